@@ -0,0 +1,252 @@
+use crate::pattern::{set_chars, DIGITS, SPACES};
+use crate::Pattern;
+use std::ops::{Bound, RangeBounds};
+
+// A pattern put together piece by piece, such as
+// `Pat::lit("id").then(Pat::digit().repeat(2..=4)).group()`, and checked by `build`.
+// Backreferences count groups by opening parenthesis over the whole built pattern.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pat(Result<Pattern, BuildError>);
+
+// Why a built pattern is invalid
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum BuildError {
+    #[error("backreference \\{0} refers to a group that doesn't exist")]
+    MissingGroup(usize),
+    #[error("backreference \\{0} comes before the group it refers to")]
+    LaterGroup(usize),
+    #[error("repeat range has no counts")]
+    EmptyRange,
+    #[error("choice has no alternatives")]
+    EmptyChoice,
+    #[error("invalid group name {0:?}")]
+    InvalidName(String),
+    #[error("group name {0:?} is used more than once")]
+    DuplicateName(String),
+    #[error("set {0:?} can't be written as a bracket expression")]
+    InvalidSet(String),
+}
+
+impl Pat {
+    // Exactly this text
+    pub fn lit(text: &str) -> Pat {
+        Pat(Ok(Pattern::literal(text)))
+    }
+
+    pub fn char(c: char) -> Pat {
+        Pat(Ok(Pattern::ExactChar(c)))
+    }
+
+    // Any character but a line terminator, as `.`
+    pub fn any() -> Pat {
+        Pat(Ok(Pattern::AnyChar))
+    }
+
+    // A word character, as `\w`
+    pub fn word() -> Pat {
+        Pat(Ok(Pattern::AlphaNumeric))
+    }
+
+    // A digit, as `\d`
+    pub fn digit() -> Pat {
+        Pat::set(DIGITS)
+    }
+
+    // A whitespace character, as `\s`
+    pub fn space() -> Pat {
+        Pat::set(SPACES)
+    }
+
+    // Any of the characters, as `[chars]`. A leading `^` is moved so it isn't read as
+    // negation, and sets holding `]`, or only `^`, can't be built.
+    pub fn set(chars: &str) -> Pat {
+        Pat::character_set(chars, false)
+    }
+
+    // Any character except these, as `[^chars]`, which can't hold `]`
+    pub fn not_set(chars: &str) -> Pat {
+        Pat::character_set(chars, true)
+    }
+
+    pub fn start_of_line() -> Pat {
+        Pat(Ok(Pattern::StartOfLine))
+    }
+
+    pub fn end_of_line() -> Pat {
+        Pat(Ok(Pattern::EndOfLine))
+    }
+
+    // Where no word character comes before, or after, as `\b{start-half}` and `\b{end-half}`
+    pub fn no_word_before() -> Pat {
+        Pat(Ok(Pattern::NoWordBefore))
+    }
+
+    pub fn no_word_after() -> Pat {
+        Pat(Ok(Pattern::NoWordAfter))
+    }
+
+    // The text matched by the 1-based capture group `group`
+    pub fn backref(group: usize) -> Pat {
+        Pat(Ok(Pattern::Backreference(group)))
+    }
+
+    // The first of the alternatives that matches, without capturing
+    pub fn any_of(alternatives: impl IntoIterator<Item = Pat>) -> Pat {
+        let mut alternatives = alternatives.into_iter();
+        match alternatives.next() {
+            Some(first) => alternatives.fold(first, Pat::or),
+            None => Pat(Err(BuildError::EmptyChoice)),
+        }
+    }
+
+    // This pattern followed by `next`
+    pub fn then(self, next: Pat) -> Pat {
+        self.combine(next, |first, next| {
+            let mut parts = match first {
+                Pattern::Sequence(parts) => parts,
+                first => vec![first],
+            };
+            match next {
+                Pattern::Sequence(next) => parts.extend(next),
+                next => parts.push(next),
+            }
+            Pattern::Sequence(parts)
+        })
+    }
+
+    // This pattern, or `alternative` where it doesn't match
+    pub fn or(self, alternative: Pat) -> Pat {
+        self.combine(alternative, |first, alternative| {
+            let mut branches = match first {
+                Pattern::OneOf(branches) => branches,
+                first => vec![first],
+            };
+            branches.push(alternative);
+            Pattern::OneOf(branches)
+        })
+    }
+
+    // Between the bounds of `counts` repetitions, as many as possible
+    pub fn repeat(self, counts: impl RangeBounds<usize>) -> Pat {
+        let min = match counts.start_bound() {
+            Bound::Included(&min) => Some(min),
+            Bound::Excluded(&min) => min.checked_add(1),
+            Bound::Unbounded => Some(0),
+        };
+        let max = match counts.end_bound() {
+            Bound::Included(&max) => Some(Some(max)),
+            Bound::Excluded(&max) => max.checked_sub(1).map(Some),
+            Bound::Unbounded => Some(None),
+        };
+        match (min, max) {
+            (Some(min), Some(max)) if max.map_or(true, |max| min <= max) => self.map(|pattern| Pattern::Repeated {
+                min,
+                max,
+                pattern: Box::new(pattern),
+            }),
+            _ => Pat(self.0.and(Err(BuildError::EmptyRange))),
+        }
+    }
+
+    // Any number of repetitions, as `*`
+    pub fn zero_or_more(self) -> Pat {
+        self.repeat(..)
+    }
+
+    // At least one repetition, as `+`
+    pub fn one_or_more(self) -> Pat {
+        self.map(|pattern| Pattern::OneOrMore(Box::new(pattern)))
+    }
+
+    // At most one repetition, as `?`
+    pub fn optional(self) -> Pat {
+        self.map(|pattern| Pattern::ZeroOrOne(Box::new(pattern)))
+    }
+
+    // A capture group, numbered by where it starts in the built pattern
+    pub fn group(self) -> Pat {
+        self.map(|pattern| {
+            if pattern.has_capture_group() {
+                Pattern::NestedCapture(Box::new(pattern))
+            } else {
+                Pattern::CaptureGroup(Box::new(pattern))
+            }
+        })
+    }
+
+    // A capture group that can also be referred to by name
+    pub fn named(self, name: &str) -> Pat {
+        let name = name.to_string();
+        self.map(|pattern| Pattern::NamedCapture {
+            name,
+            pattern: Box::new(pattern),
+        })
+    }
+
+    // The pattern, once its backreferences and group names are checked
+    pub fn build(self) -> Result<Pattern, BuildError> {
+        let pattern = self.0?;
+        let group_count = pattern.group_count();
+        let mut names: Vec<&str> = vec![];
+        for name in pattern.capture_names().into_iter().flatten() {
+            if !is_group_name(name) {
+                return Err(BuildError::InvalidName(name.to_string()));
+            }
+            if names.contains(&name) {
+                return Err(BuildError::DuplicateName(name.to_string()));
+            }
+            names.push(name);
+        }
+        check_backreferences(&pattern, group_count, &mut 0)?;
+        Ok(pattern)
+    }
+
+    fn character_set(chars: &str, negated: bool) -> Pat {
+        match set_chars(chars, negated) {
+            Some(chars) => Pat(Ok(Pattern::CharacterSet { chars, negated })),
+            None => Pat(Err(BuildError::InvalidSet(chars.to_string()))),
+        }
+    }
+
+    fn map(self, f: impl FnOnce(Pattern) -> Pattern) -> Pat {
+        Pat(self.0.map(f))
+    }
+
+    fn combine(self, other: Pat, f: impl FnOnce(Pattern, Pattern) -> Pattern) -> Pat {
+        Pat(self.0.and_then(|first| other.0.map(|other| f(first, other))))
+    }
+}
+
+// An existing pattern to build on, such as a parsed one
+impl From<Pattern> for Pat {
+    fn from(pattern: Pattern) -> Self {
+        Pat(Ok(pattern))
+    }
+}
+
+// A name the parser accepts in `(?<name>...)`
+fn is_group_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Walks groups in the order they're numbered, counting those opened so far, so
+// a backreference can only refer to a group it's inside of or one before it
+fn check_backreferences(pattern: &Pattern, group_count: usize, opened: &mut usize) -> Result<(), BuildError> {
+    match pattern {
+        Pattern::Backreference(n) if *n == 0 || *n > group_count => Err(BuildError::MissingGroup(*n)),
+        Pattern::Backreference(n) if *n > *opened => Err(BuildError::LaterGroup(*n)),
+        Pattern::Sequence(patterns) | Pattern::OneOf(patterns) | Pattern::Alternation(patterns) => patterns
+            .iter()
+            .try_for_each(|pattern| check_backreferences(pattern, group_count, opened)),
+        Pattern::Repeated { pattern, .. } | Pattern::OneOrMore(pattern) | Pattern::ZeroOrOne(pattern) => {
+            check_backreferences(pattern, group_count, opened)
+        }
+        Pattern::CaptureGroup(pattern) | Pattern::NestedCapture(pattern) | Pattern::NamedCapture { pattern, .. } => {
+            *opened += 1;
+            check_backreferences(pattern, group_count, opened)
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt::Display;
+use std::io::{self, Write};
+
+// When to color the output
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" | "tty" | "if-tty" => Ok(ColorChoice::Auto),
+            "always" | "yes" | "force" => Ok(ColorChoice::Always),
+            "never" | "no" | "none" => Ok(ColorChoice::Never),
+            _ => Err(format!("Invalid color choice {:?}", s)),
+        }
+    }
+}
+
+// SGR sequences for each part of the output, configured like GNU grep's GREP_COLORS
+#[derive(Clone, Debug, PartialEq)]
+pub struct Colors {
+    pub selected_match: String,
+    pub context_match: String,
+    pub selected_line: String,
+    pub context_line: String,
+    pub filename: String,
+    pub line_number: String,
+    pub byte_offset: String,
+    pub separator: String,
+    // Whether to erase to the end of line after each sequence, which `ne` turns off
+    pub erase_line: bool,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors {
+            selected_match: "01;31".to_string(),
+            context_match: "01;31".to_string(),
+            selected_line: String::new(),
+            context_line: String::new(),
+            filename: "35".to_string(),
+            line_number: "32".to_string(),
+            byte_offset: "32".to_string(),
+            separator: "36".to_string(),
+            erase_line: true,
+        }
+    }
+}
+
+impl Colors {
+    // Apply a GREP_COLORS specification such as `ms=01;31:fn=35:ne` over the defaults.
+    // Unknown capabilities are ignored, as GNU grep does.
+    pub fn parse(spec: &str) -> Colors {
+        let mut colors = Colors::default();
+        for capability in spec.split(':') {
+            let (name, value) = match capability.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (capability, None),
+            };
+            match (name, value) {
+                ("mt", Some(value)) => {
+                    colors.selected_match = value.clone();
+                    colors.context_match = value;
+                }
+                ("ms", Some(value)) => colors.selected_match = value,
+                ("mc", Some(value)) => colors.context_match = value,
+                ("sl", Some(value)) => colors.selected_line = value,
+                ("cx", Some(value)) => colors.context_line = value,
+                ("fn", Some(value)) => colors.filename = value,
+                ("ln", Some(value)) => colors.line_number = value,
+                ("bn", Some(value)) => colors.byte_offset = value,
+                ("se", Some(value)) => colors.separator = value,
+                ("ne", None) => colors.erase_line = false,
+                _ => {}
+            }
+        }
+        colors
+    }
+
+    // Write `text` wrapped in the SGR sequence `sgr`, or plainly if it is empty
+    pub fn paint(&self, out: &mut dyn Write, sgr: &str, text: impl Display) -> io::Result<()> {
+        if sgr.is_empty() {
+            return write!(out, "{}", text);
+        }
+        let erase = if self.erase_line { "\x1b[K" } else { "" };
+        write!(out, "\x1b[{}m{}{}\x1b[m{}", sgr, erase, text, erase)
+    }
+
+    // Like `paint`, for input bytes that need not be UTF-8
+    pub fn paint_bytes(&self, out: &mut dyn Write, sgr: &str, bytes: &[u8]) -> io::Result<()> {
+        if sgr.is_empty() {
+            return out.write_all(bytes);
+        }
+        let erase = if self.erase_line { "\x1b[K" } else { "" };
+        write!(out, "\x1b[{}m{}", sgr, erase)?;
+        out.write_all(bytes)?;
+        write!(out, "\x1b[m{}", erase)
+    }
+}
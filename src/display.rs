@@ -0,0 +1,123 @@
+use crate::pattern::{DIGITS, SPACES};
+use crate::Pattern;
+use std::fmt::{self, Display, Formatter, Write};
+
+// Regex syntax that parses back to an equal pattern, for any pattern the parser produces
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write_branch(f, self)
+    }
+}
+
+// The text with its metacharacters quoted, as a regex matching exactly that text
+pub fn escape(text: &str) -> String {
+    Pattern::literal(text).to_string()
+}
+
+fn write_branches(f: &mut Formatter, branches: &[Pattern]) -> fmt::Result {
+    for (i, branch) in branches.iter().enumerate() {
+        if i > 0 {
+            f.write_char('|')?;
+        }
+        match branch {
+            // A choice within a choice keeps its own parentheses
+            Pattern::OneOf(_) | Pattern::Alternation(_) => write_part(f, branch, true, true)?,
+            branch => write_branch(f, branch)?,
+        }
+    }
+    Ok(())
+}
+
+// The whole regex or the contents of a group, where neither a sequence nor a choice
+// needs parentheses
+fn write_branch(f: &mut Formatter, pattern: &Pattern) -> fmt::Result {
+    match pattern {
+        Pattern::OneOf(branches) | Pattern::Alternation(branches) => write_branches(f, branches),
+        Pattern::Sequence(parts) => {
+            for (i, part) in parts.iter().enumerate() {
+                write_part(f, part, i == 0, i + 1 == parts.len())?;
+            }
+            Ok(())
+        }
+        pattern => write_part(f, pattern, true, true),
+    }
+}
+
+// One part of a branch. Anchors are only bare at the start or end of the branch,
+// where the parser reads them as anchors.
+fn write_part(f: &mut Formatter, pattern: &Pattern, first: bool, last: bool) -> fmt::Result {
+    match pattern {
+        Pattern::ExactChar(c) => write_char(f, *c),
+        Pattern::Literal(literal) => literal.chars().try_for_each(|c| write_char(f, c)),
+        Pattern::AnyChar => f.write_char('.'),
+        Pattern::AlphaNumeric => f.write_str("\\w"),
+        Pattern::CharacterSet { chars, negated } => match (chars.as_str(), negated) {
+            (DIGITS, false) => f.write_str("\\d"),
+            (DIGITS, true) => f.write_str("\\D"),
+            (SPACES, false) => f.write_str("\\s"),
+            (SPACES, true) => f.write_str("\\S"),
+            (chars, true) => write!(f, "[^{}]", chars),
+            (chars, false) => write!(f, "[{}]", chars),
+        },
+        Pattern::StartOfLine if first => f.write_char('^'),
+        Pattern::EndOfLine if last => f.write_char('$'),
+        Pattern::NoWordBefore => f.write_str("\\b{start-half}"),
+        Pattern::NoWordAfter => f.write_str("\\b{end-half}"),
+        Pattern::Backreference(n) => write!(f, "\\{}", n),
+        Pattern::Repeated { min, max, pattern } => {
+            write_operand(f, pattern)?;
+            match (min, max) {
+                (0, None) => f.write_char('*'),
+                (min, None) => write!(f, "{{{},}}", min),
+                (min, Some(max)) if min == max => write!(f, "{{{}}}", min),
+                (min, Some(max)) => write!(f, "{{{},{}}}", min, max),
+            }
+        }
+        Pattern::OneOrMore(pattern) => {
+            write_operand(f, pattern)?;
+            f.write_char('+')
+        }
+        Pattern::ZeroOrOne(pattern) => {
+            write_operand(f, pattern)?;
+            f.write_char('?')
+        }
+        Pattern::CaptureGroup(pattern) | Pattern::NestedCapture(pattern) => {
+            f.write_char('(')?;
+            write_branch(f, pattern)?;
+            f.write_char(')')
+        }
+        Pattern::NamedCapture { name, pattern } => {
+            write!(f, "(?<{}>", name)?;
+            write_branch(f, pattern)?;
+            f.write_char(')')
+        }
+        // Nested sequences, choices and anchors elsewhere in a branch
+        pattern => {
+            f.write_str("(?:")?;
+            write_branch(f, pattern)?;
+            f.write_char(')')
+        }
+    }
+}
+
+// The pattern a quantifier applies to, grouped if it is quantified itself or several characters
+fn write_operand(f: &mut Formatter, pattern: &Pattern) -> fmt::Result {
+    match pattern {
+        Pattern::Repeated { .. } | Pattern::OneOrMore(_) | Pattern::ZeroOrOne(_) | Pattern::Literal(_) => {
+            f.write_str("(?:")?;
+            write_branch(f, pattern)?;
+            f.write_char(')')
+        }
+        pattern => write_part(f, pattern, false, false),
+    }
+}
+
+fn write_char(f: &mut Formatter, c: char) -> fmt::Result {
+    match c {
+        '\\' | '.' | '^' | '$' | '|' | '?' | '*' | '+' | '(' | ')' | '[' | ']' | '{' | '}' => write!(f, "\\{}", c),
+        '\n' => f.write_str("\\n"),
+        '\t' => f.write_str("\\t"),
+        '\r' => f.write_str("\\r"),
+        c => f.write_char(c),
+    }
+}
@@ -0,0 +1,191 @@
+use crate::Pattern;
+
+// Facts about a pattern that hold for all of its matches
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatternInfo {
+    // Length of the shortest and longest match in characters, None if unbounded
+    pub min_len: usize,
+    pub max_len: Option<usize>,
+    // Whether every match starts at the start of a line, or ends at the end of one
+    pub anchored_start: bool,
+    pub anchored_end: bool,
+    // Whether some match can be empty
+    pub matches_empty: bool,
+    // Whether an empty match is found in any line at all, so that every line is selected
+    pub matches_every_line: bool,
+    pub group_count: usize,
+    // Names of the capture groups in group order, None for unnamed groups
+    pub group_names: Vec<Option<String>>,
+    pub has_backreferences: bool,
+}
+
+// What is known about the matches of one node
+#[derive(Clone, Copy)]
+struct Facts {
+    min_len: usize,
+    max_len: Option<usize>,
+    anchored_start: bool,
+    anchored_end: bool,
+    // Whether it can match empty at the start, or at the end, of any line
+    empty_at_start: bool,
+    empty_at_end: bool,
+}
+
+impl Pattern {
+    pub fn info(&self) -> PatternInfo {
+        // Backreferences match what their group matched, so group lengths come first
+        let mut groups = vec![];
+        collect_groups(self, &mut groups);
+        let group_lengths: Vec<_> = groups
+            .iter()
+            .map(|group| {
+                let facts = analyze(group, &[]);
+                (facts.min_len, facts.max_len)
+            })
+            .collect();
+        let facts = analyze(self, &group_lengths);
+
+        PatternInfo {
+            min_len: facts.min_len,
+            max_len: facts.max_len,
+            anchored_start: facts.anchored_start,
+            anchored_end: facts.anchored_end,
+            matches_empty: facts.min_len == 0,
+            matches_every_line: facts.empty_at_start || facts.empty_at_end,
+            group_count: self.group_count(),
+            group_names: self.capture_names().into_iter().map(|name| name.map(str::to_string)).collect(),
+            has_backreferences: has_backreferences(self),
+        }
+    }
+}
+
+// The pattern each capture group matches, in group order
+fn collect_groups<'p>(pattern: &'p Pattern, groups: &mut Vec<&'p Pattern>) {
+    match pattern {
+        Pattern::Sequence(patterns) | Pattern::OneOf(patterns) | Pattern::Alternation(patterns) => {
+            patterns.iter().for_each(|p| collect_groups(p, groups));
+        }
+        Pattern::Repeated { pattern, .. } | Pattern::OneOrMore(pattern) | Pattern::ZeroOrOne(pattern) => {
+            collect_groups(pattern, groups);
+        }
+        Pattern::CaptureGroup(inner) | Pattern::NestedCapture(inner) | Pattern::NamedCapture { pattern: inner, .. } => {
+            groups.push(inner);
+            collect_groups(inner, groups);
+        }
+        _ => {}
+    }
+}
+
+// Facts about a node, given the lengths of the groups backreferences refer to
+fn analyze(pattern: &Pattern, group_lengths: &[(usize, Option<usize>)]) -> Facts {
+    let fixed = |len: usize| Facts {
+        min_len: len,
+        max_len: Some(len),
+        anchored_start: false,
+        anchored_end: false,
+        empty_at_start: len == 0,
+        empty_at_end: len == 0,
+    };
+    // A zero-width assertion that holds at the start or end of every line
+    let assertion = |at_start: bool, at_end: bool| Facts {
+        empty_at_start: at_start,
+        empty_at_end: at_end,
+        ..fixed(0)
+    };
+
+    match pattern {
+        Pattern::ExactChar(_) | Pattern::AnyChar | Pattern::AlphaNumeric | Pattern::CharacterSet { .. } => fixed(1),
+        Pattern::Literal(literal) => fixed(literal.chars().count()),
+        Pattern::StartOfLine => Facts {
+            anchored_start: true,
+            ..assertion(true, false)
+        },
+        Pattern::EndOfLine => Facts {
+            anchored_end: true,
+            ..assertion(false, true)
+        },
+        Pattern::NoWordBefore => assertion(true, false),
+        Pattern::NoWordAfter => assertion(false, true),
+        Pattern::Backreference(n) => {
+            let (min_len, max_len) = n
+                .checked_sub(1)
+                .and_then(|index| group_lengths.get(index).copied())
+                .unwrap_or((0, None));
+            Facts {
+                min_len,
+                max_len,
+                ..fixed(1)
+            }
+        }
+        Pattern::Sequence(parts) => {
+            let facts: Vec<_> = parts.iter().map(|part| analyze(part, group_lengths)).collect();
+            let zero_width = |facts: &&Facts| facts.max_len == Some(0);
+            Facts {
+                min_len: facts.iter().fold(0, |sum, f| sum.saturating_add(f.min_len)),
+                max_len: facts.iter().try_fold(0, |sum: usize, f| f.max_len.map(|max| sum.saturating_add(max))),
+                // Assertions may come before the anchor, but nothing that consumes text
+                anchored_start: facts
+                    .iter()
+                    .take_while(|f| zero_width(f) || f.anchored_start)
+                    .any(|f| f.anchored_start),
+                anchored_end: facts
+                    .iter()
+                    .rev()
+                    .take_while(|f| zero_width(f) || f.anchored_end)
+                    .any(|f| f.anchored_end),
+                empty_at_start: facts.iter().all(|f| f.empty_at_start),
+                empty_at_end: facts.iter().all(|f| f.empty_at_end),
+            }
+        }
+        Pattern::OneOf(branches) | Pattern::Alternation(branches) => {
+            let facts: Vec<_> = branches.iter().map(|branch| analyze(branch, group_lengths)).collect();
+            Facts {
+                min_len: facts.iter().map(|f| f.min_len).min().unwrap_or(0),
+                max_len: facts.iter().try_fold(0, |longest: usize, f| f.max_len.map(|max| longest.max(max))),
+                anchored_start: facts.iter().all(|f| f.anchored_start),
+                anchored_end: facts.iter().all(|f| f.anchored_end),
+                empty_at_start: facts.iter().any(|f| f.empty_at_start),
+                empty_at_end: facts.iter().any(|f| f.empty_at_end),
+            }
+        }
+        Pattern::Repeated { min, max, pattern } => repeat(analyze(pattern, group_lengths), *min, *max),
+        Pattern::OneOrMore(pattern) => repeat(analyze(pattern, group_lengths), 1, None),
+        Pattern::ZeroOrOne(pattern) => repeat(analyze(pattern, group_lengths), 0, Some(1)),
+        Pattern::CaptureGroup(pattern) | Pattern::NestedCapture(pattern) | Pattern::NamedCapture { pattern, .. } => {
+            analyze(pattern, group_lengths)
+        }
+    }
+}
+
+// Facts about between `min` and `max` repetitions of a node
+fn repeat(facts: Facts, min: usize, max: Option<usize>) -> Facts {
+    let max_len = match (facts.max_len, max) {
+        (Some(0), _) | (_, Some(0)) => Some(0),
+        (Some(len), Some(max)) => Some(len.saturating_mul(max)),
+        _ => None,
+    };
+    Facts {
+        min_len: facts.min_len.saturating_mul(min),
+        max_len,
+        anchored_start: min > 0 && facts.anchored_start,
+        anchored_end: min > 0 && facts.anchored_end,
+        empty_at_start: min == 0 || facts.empty_at_start,
+        empty_at_end: min == 0 || facts.empty_at_end,
+    }
+}
+
+fn has_backreferences(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::Backreference(_) => true,
+        Pattern::Sequence(patterns) | Pattern::OneOf(patterns) | Pattern::Alternation(patterns) => {
+            patterns.iter().any(has_backreferences)
+        }
+        Pattern::Repeated { pattern, .. }
+        | Pattern::OneOrMore(pattern)
+        | Pattern::ZeroOrOne(pattern)
+        | Pattern::CaptureGroup(pattern)
+        | Pattern::NestedCapture(pattern)
+        | Pattern::NamedCapture { pattern, .. } => has_backreferences(pattern),
+        _ => false,
+    }
+}
@@ -0,0 +1,150 @@
+use crate::Pattern;
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::time::Duration;
+
+// A JSON value, written compactly on a single line
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value<'a> {
+    Null,
+    Bool(bool),
+    Number(u64),
+    String(Cow<'a, str>),
+    Array(Vec<Value<'a>>),
+    // Members are written in the given order
+    Object(Vec<(&'static str, Value<'a>)>),
+}
+
+impl Value<'_> {
+    pub fn write(&self, out: &mut dyn Write) -> io::Result<()> {
+        match self {
+            Value::Null => out.write_all(b"null"),
+            Value::Bool(b) => write!(out, "{}", b),
+            Value::Number(n) => write!(out, "{}", n),
+            Value::String(s) => write_string(out, s),
+            Value::Array(values) => {
+                out.write_all(b"[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        out.write_all(b",")?;
+                    }
+                    value.write(out)?;
+                }
+                out.write_all(b"]")
+            }
+            Value::Object(members) => {
+                out.write_all(b"{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        out.write_all(b",")?;
+                    }
+                    write_string(out, key)?;
+                    out.write_all(b":")?;
+                    value.write(out)?;
+                }
+                out.write_all(b"}")
+            }
+        }
+    }
+}
+
+// Arbitrary data as ripgrep encodes it: `{"text":...}` when it is valid UTF-8,
+// otherwise `{"bytes":...}` holding its base64 encoding
+pub fn data(bytes: &[u8]) -> Value<'_> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Value::Object(vec![("text", Value::String(Cow::Borrowed(text)))]),
+        Err(_) => Value::Object(vec![("bytes", Value::String(Cow::Owned(base64(bytes))))]),
+    }
+}
+
+// A duration as ripgrep reports it, with a human-readable form in seconds
+pub fn duration(elapsed: Duration) -> Value<'static> {
+    Value::Object(vec![
+        ("secs", Value::Number(elapsed.as_secs())),
+        ("nanos", Value::Number(u64::from(elapsed.subsec_nanos()))),
+        ("human", Value::String(Cow::Owned(format!("{:.6}s", elapsed.as_secs_f64())))),
+    ])
+}
+
+// A pattern in the same schema as its serde serialization: each variant is its
+// snake_case name, holding its contents as `{"name":contents}` if it has any
+pub fn pattern(pattern: &Pattern) -> Value<'static> {
+    let string = |s: &str| Value::String(Cow::Owned(s.to_string()));
+    let number = |n: usize| Value::Number(n as u64);
+    let list = |patterns: &[Pattern]| Value::Array(patterns.iter().map(self::pattern).collect());
+    let (name, contents) = match pattern {
+        Pattern::ExactChar(c) => ("exact_char", string(&c.to_string())),
+        Pattern::Literal(literal) => ("literal", string(literal)),
+        Pattern::AnyChar => return string("any_char"),
+        Pattern::AlphaNumeric => return string("alpha_numeric"),
+        Pattern::Sequence(patterns) => ("sequence", list(patterns)),
+        Pattern::Repeated { min, max, pattern } => (
+            "repeated",
+            Value::Object(vec![
+                ("min", number(*min)),
+                ("max", max.map_or(Value::Null, number)),
+                ("pattern", self::pattern(pattern)),
+            ]),
+        ),
+        Pattern::OneOf(patterns) => ("one_of", list(patterns)),
+        Pattern::Alternation(patterns) => ("alternation", list(patterns)),
+        Pattern::CharacterSet { chars, negated } => (
+            "character_set",
+            Value::Object(vec![("chars", string(chars)), ("negated", Value::Bool(*negated))]),
+        ),
+        Pattern::StartOfLine => return string("start_of_line"),
+        Pattern::EndOfLine => return string("end_of_line"),
+        Pattern::NoWordBefore => return string("no_word_before"),
+        Pattern::NoWordAfter => return string("no_word_after"),
+        Pattern::OneOrMore(pattern) => ("one_or_more", self::pattern(pattern)),
+        Pattern::ZeroOrOne(pattern) => ("zero_or_one", self::pattern(pattern)),
+        Pattern::Backreference(n) => ("backreference", number(*n)),
+        Pattern::CaptureGroup(pattern) => ("capture_group", self::pattern(pattern)),
+        Pattern::NestedCapture(pattern) => ("nested_capture", self::pattern(pattern)),
+        Pattern::NamedCapture { name, pattern } => (
+            "named_capture",
+            Value::Object(vec![("name", string(name)), ("pattern", self::pattern(pattern))]),
+        ),
+    };
+    Value::Object(vec![(name, contents)])
+}
+
+fn write_string(out: &mut dyn Write, s: &str) -> io::Result<()> {
+    out.write_all(b"\"")?;
+    let mut last = 0;
+    for (i, c) in s.char_indices() {
+        if c != '"' && c != '\\' && c >= ' ' {
+            continue;
+        }
+        out.write_all(&s.as_bytes()[last..i])?;
+        match c {
+            '"' => out.write_all(b"\\\"")?,
+            '\\' => out.write_all(b"\\\\")?,
+            '\n' => out.write_all(b"\\n")?,
+            '\r' => out.write_all(b"\\r")?,
+            '\t' => out.write_all(b"\\t")?,
+            _ => write!(out, "\\u{:04x}", u32::from(c))?,
+        }
+        // Every escaped character is a single byte
+        last = i + 1;
+    }
+    out.write_all(&s.as_bytes()[last..])?;
+    out.write_all(b"\"")
+}
+
+// Standard base64 with padding
+pub fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(char::from(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
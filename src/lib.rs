@@ -1,49 +1,210 @@
 mod pattern;
 mod parser;
+mod display;
+mod normalize;
+mod info;
+mod redos;
+mod builder;
 mod matcher;
+mod regex;
+mod nfa;
+mod set;
+mod color;
+mod options;
+mod reader;
+mod search;
+mod json;
+mod replace;
 
 pub use pattern::Pattern;
 pub use parser::parse_pattern;
-pub use matcher::Matcher;
+pub use display::escape;
+pub use builder::{BuildError, Pat};
+pub use info::PatternInfo;
+pub use redos::BacktrackRisk;
+pub use regex::{Regex, RegexBuilder, Split, SplitN};
+pub use set::RegexSet;
+pub use matcher::{Captures, CapturesIter, FindIter, MatchConfig, MatchError, Matcher};
 
+use color::{ColorChoice, Colors};
+use options::{AstFormat, Options, OutputMode};
+use search::Searcher;
 use std::env;
-use std::io::{self, Read};
+use std::fs;
+use std::io::{self, BufWriter, IsTerminal, LineWriter, Read, Write};
 use std::error::Error;
+use std::time::Instant;
 
-pub fn run() -> Result<(), Box<dyn Error>> {
-    // Check if the first argument is '-E'
-    if env::args().nth(1).ok_or("No arguments provided")? != "-E" {
-        return Err("Expected first argument to be '-E'".into());
+// Search the inputs named on the command line, returning grep's exit status
+pub fn run() -> Result<i32, Box<dyn Error>> {
+    let options = Options::parse(env::args().skip(1))?;
+    if options.show_help {
+        println!("{}\n{}", options::USAGE, options::HELP);
+        return Ok(0);
     }
+    if options.show_version {
+        println!("grep (codecrafters-grep) {}", env!("CARGO_PKG_VERSION"));
+        return Ok(0);
+    }
+    log::debug!("Pattern strings: {:?}", options.patterns);
+    let parsed = parse_patterns(&options)?;
+    log::debug!("Parsed pattern: {:?}", parsed);
+    if let Some(AstFormat::Json) = options.dump_ast {
+        let mut out = io::stdout().lock();
+        json::pattern(&parsed).write(&mut out)?;
+        writeln!(out)?;
+        return Ok(0);
+    }
+    let pattern = search_pattern(&options, parsed);
 
-    // Get the pattern from the second argument
-    let pattern_str = env::args().nth(2).ok_or("No pattern provided")?;
-    log::debug!("Pattern string: {:?}", pattern_str);
-    let pattern = parse_pattern(&pattern_str)?;
-    log::debug!("Parsed pattern: {:?}", pattern);
-
-    // Read input
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+    let files = if options.files.is_empty() {
+        vec!["-".to_string()]
+    } else {
+        options.files.clone()
+    };
+    let with_filename = options.with_filename.unwrap_or(files.len() > 1 || options.vimgrep);
+    let mut searcher = Searcher::new(&pattern, &options, with_filename);
 
-    // Remove trailing newline if present
-    let input = input.trim_end();
-    log::debug!("Input: {:?}", input);
+    // Flush after every line for a terminal, in blocks otherwise
+    let stdout = io::stdout();
+    let use_color = match options.color {
+        ColorChoice::Always => true,
+        ColorChoice::Auto => stdout.is_terminal() && env::var("TERM").map_or(true, |term| term != "dumb"),
+        ColorChoice::Never => false,
+    };
+    if use_color {
+        let colors = env::var("GREP_COLORS").map_or_else(|_| Colors::default(), |spec| Colors::parse(&spec));
+        searcher = searcher.with_colors(colors);
+    }
+    let mut out: Box<dyn Write> = if stdout.is_terminal() {
+        Box::new(LineWriter::new(stdout.lock()))
+    } else {
+        Box::new(BufWriter::new(stdout.lock()))
+    };
 
-    let has_match = Matcher::match_str(&pattern, input);
+    let started = Instant::now();
+    let mut has_match = false;
+    let mut has_error = false;
+    for file in &files {
+        let result = if file == "-" {
+            searcher.search("(standard input)", io::stdin().lock(), &mut out)
+        } else {
+            search_file(&searcher, &options, file, &mut out)
+        };
+        match result {
+            Ok(count) if count > 0 => {
+                has_match = true;
+                if options.output == OutputMode::Quiet {
+                    break;
+                }
+            }
+            Ok(_) => {}
+            // The reader of our output has gone away
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => break,
+            Err(e) => {
+                out.flush()?;
+                eprintln!("Error: {}: {}", file, e);
+                has_error = true;
+            }
+        }
+    }
+    let finished = if options.json && options.output != OutputMode::Quiet {
+        searcher.write_summary(&mut out, started.elapsed()).and_then(|_| out.flush())
+    } else {
+        out.flush()
+    };
+    match finished {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e.into()),
+        _ => {}
+    }
     log::debug!("Match result: {}", has_match);
-    
-    if has_match {
-        println!("Pattern matches!");
-        Ok(())
+
+    // Exit status follows grep: 0 on a match, 1 on no match, 2 on error
+    Ok(match (has_match, has_error) {
+        (true, _) if options.output == OutputMode::Quiet => 0,
+        (_, true) => 2,
+        (true, false) => 0,
+        (false, false) => 1,
+    })
+}
+
+// Files at least this large are memory-mapped unless --mmap or --no-mmap says otherwise
+const MMAP_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+// Search a file as a stream, or through a memory map when that is chosen
+fn search_file(searcher: &Searcher, options: &Options, path: &str, out: &mut dyn Write) -> io::Result<usize> {
+    let file = fs::File::open(path)?;
+    let metadata = file.metadata()?;
+    let use_mmap = options.mmap.unwrap_or(metadata.len() >= MMAP_THRESHOLD);
+    if use_mmap && metadata.is_file() && metadata.len() > 0 {
+        // Safety: the map is only read, but its contents can change if another process
+        // modifies the file during the search. Like other mmap-based tools we accept that.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        searcher.search_buffer(path, &map, out)
+    } else {
+        searcher.search(path, file, out)
+    }
+}
+
+// The patterns given in the options combined into one, as they were written
+fn parse_patterns(options: &Options) -> Result<Pattern, Box<dyn Error>> {
+    let mut patterns = options
+        .patterns
+        .iter()
+        .map(|s| pattern_from(options, s))
+        .collect::<Result<Vec<_>, _>>()?;
+    for file in &options.pattern_files {
+        let contents = if file == "-" {
+            let mut contents = String::new();
+            io::stdin().read_to_string(&mut contents)?;
+            contents
+        } else {
+            fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?
+        };
+        for line in contents.lines() {
+            patterns.push(pattern_from(options, line)?);
+        }
+    }
+    Ok(Pattern::any_of(patterns))
+}
+
+// The pattern to search for, matching whole words or lines if requested
+fn search_pattern(options: &Options, pattern: Pattern) -> Pattern {
+    let pattern = if options.line_regexp {
+        pattern.whole_line()
+    } else if options.word_regexp {
+        pattern.whole_word()
+    } else {
+        pattern
+    };
+    pattern.normalize()
+}
+
+// A pattern string as a regex, or as plain text with -F
+fn pattern_from(options: &Options, s: &str) -> Result<Pattern, String> {
+    if options.fixed_strings {
+        Ok(Pattern::literal(s))
     } else {
-        Err("Pattern does not match.".into())
+        parse_pattern(s)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    mod builder_tests;
+    mod color_tests;
+    mod display_tests;
+    mod info_tests;
+    mod json_tests;
     mod matcher_tests;
+    mod normalize_tests;
+    mod options_tests;
     mod parser_tests;
     mod pattern_tests;
-}
\ No newline at end of file
+    mod reader_tests;
+    mod redos_tests;
+    mod regex_tests;
+    mod replace_tests;
+    mod search_tests;
+    mod set_tests;
+}
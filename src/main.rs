@@ -2,10 +2,13 @@ use std::process;
 use codecrafters_grep::run;
 
 fn main() {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug")).init();
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
 
-    if let Err(e) = run() {
-        eprintln!("Error: {}", e);
-        process::exit(1);
+    match run() {
+        Ok(status) => process::exit(status),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(2);
+        }
     }
 }
@@ -1,186 +1,415 @@
 use crate::Pattern;
+use std::ops::Range;
 
 pub struct Matcher;
 
+// How text that may span several lines is matched
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchConfig {
+    // Ends each line of the text. Anchors match around it and no match crosses it.
+    // Without one the whole text is a single line.
+    pub line_terminator: Option<char>,
+    // A carriage return right before a line terminator or the end of the text is part
+    // of the line ending, so `$` matches before it and no match consumes it
+    pub crlf: bool,
+    // Matches may span lines. Only `.` still never matches a line terminator.
+    pub multiline: bool,
+}
+
+// Why matching gave up without an answer
+#[derive(Clone, Copy, Debug, PartialEq, thiserror::Error)]
+pub enum MatchError {
+    // Matching took more backtracking steps than its limit allows
+    #[error("match exceeded its backtracking limit")]
+    BudgetExceeded,
+}
+
+// Byte spans of a match and of its capture groups
+#[derive(Clone, Debug, PartialEq)]
+pub struct Captures {
+    // Indexed by group number, with the whole match as group 0
+    spans: Vec<Option<Range<usize>>>,
+}
+
+// Iterator over the captures of successive non-overlapping matches in a text
+pub struct CapturesIter<'p, 't> {
+    pattern: &'p Pattern,
+    text: &'t str,
+    pos: usize,
+    last_end: Option<usize>,
+}
+
+// Iterator over successive non-overlapping matches in a text
+pub struct FindIter<'p, 't>(CapturesIter<'p, 't>);
+
+// Byte span of each capture group, indexed by group number
+type Slots = Vec<Option<(usize, usize)>>;
+
+// Continuation invoked with the position reached after a successful partial match
+type Next<'k, 't> = &'k mut dyn FnMut(&mut State<'t>, usize) -> bool;
+
+struct State<'t> {
+    text: &'t str,
+    slots: Slots,
+    config: MatchConfig,
+    // Nodes tried so far, and how many may be tried before matching gives up
+    steps: usize,
+    backtrack_limit: Option<usize>,
+}
+
 impl Matcher {
+    // Whether any newline-terminated line of the text matches
     pub fn match_str(pattern: &Pattern, s: &str) -> bool {
-        s.lines().any(|line| Self::match_line(pattern, line))
+        Self::match_records(pattern, s, '\n')
     }
 
-    fn match_line(pattern: &Pattern, line: &str) -> bool {
-        match pattern {
-            Pattern::StartOfLine => Self::match_from_start(pattern, line, &mut Vec::new()),
-            Pattern::EndOfLine => line.is_empty() || Self::match_from_start(pattern, line, &mut Vec::new()) || Self::match_from_end(pattern, line),
-            _ => (0..=line.len()).any(|i| Self::match_from_start(pattern, &line[i..], &mut Vec::new()))
+    // Whether any record of the text, each ended by `terminator`, matches
+    pub fn match_records(pattern: &Pattern, s: &str, terminator: char) -> bool {
+        s.split_terminator(terminator).any(|record| Self::is_match(pattern, record))
+    }
+
+    // Whether the pattern matches anywhere within a single line
+    pub fn is_match(pattern: &Pattern, line: &str) -> bool {
+        Self::find(pattern, line).is_some()
+    }
+
+    // Byte span of the leftmost match in the text
+    pub fn find(pattern: &Pattern, text: &str) -> Option<Range<usize>> {
+        Self::find_at(pattern, text, 0)
+    }
+
+    // Byte span of the leftmost match starting at or after `start`
+    pub fn find_at(pattern: &Pattern, text: &str, start: usize) -> Option<Range<usize>> {
+        Self::find_with(pattern, text, start, MatchConfig::default())
+    }
+
+    // Byte span of the leftmost match starting at or after `start`, matched as configured
+    pub fn find_with(pattern: &Pattern, text: &str, start: usize, config: MatchConfig) -> Option<Range<usize>> {
+        Self::captures_with(pattern, text, start, config).and_then(|captures| captures.get(0))
+    }
+
+    // Captures of the leftmost match in the text
+    pub fn captures(pattern: &Pattern, text: &str) -> Option<Captures> {
+        Self::captures_with(pattern, text, 0, MatchConfig::default())
+    }
+
+    // Captures of the leftmost match starting at or after `start`, matched as configured
+    pub fn captures_with(pattern: &Pattern, text: &str, start: usize, config: MatchConfig) -> Option<Captures> {
+        // Without a limit matching always finishes
+        Self::captures_limited(pattern, text, start, config, None).unwrap_or_default()
+    }
+
+    // Captures of the leftmost match starting at or after `start`, giving up once
+    // `backtrack_limit` nodes have been tried over all start positions
+    pub(crate) fn captures_limited(
+        pattern: &Pattern,
+        text: &str,
+        start: usize,
+        config: MatchConfig,
+        backtrack_limit: Option<usize>,
+    ) -> Result<Option<Captures>, MatchError> {
+        let mut state = State {
+            text,
+            slots: vec![None; pattern.group_count()],
+            config,
+            steps: 0,
+            backtrack_limit,
+        };
+        let starts = text[start..]
+            .char_indices()
+            .map(|(i, _)| start + i)
+            .chain(Some(text.len()));
+        for start in starts {
+            let mut captures = None;
+            let matched = Self::match_here(pattern, 0, &mut state, start, &mut |state, end| {
+                let groups = state.slots.iter().map(|slot| slot.map(|(start, end)| start..end));
+                captures = Some(Captures {
+                    spans: std::iter::once(Some(start..end)).chain(groups).collect(),
+                });
+                true
+            });
+            if matched {
+                return Ok(captures);
+            }
+            if state.exceeded() {
+                return Err(MatchError::BudgetExceeded);
+            }
+        }
+        Ok(None)
+    }
+
+    // All non-overlapping matches in the text, from left to right
+    pub fn find_iter<'p, 't>(pattern: &'p Pattern, text: &'t str) -> FindIter<'p, 't> {
+        FindIter(Self::captures_iter(pattern, text))
+    }
+
+    // Captures of all non-overlapping matches in the text, from left to right
+    pub fn captures_iter<'p, 't>(pattern: &'p Pattern, text: &'t str) -> CapturesIter<'p, 't> {
+        CapturesIter {
+            pattern,
+            text,
+            pos: 0,
+            last_end: None,
         }
     }
 
-    fn match_from_start(pattern: &Pattern, data: &str, captured_groups: &mut Vec<String>) -> bool {
+    // Match `pattern` at `pos`, with its first capture group numbered `group`
+    fn match_here<'t>(pattern: &Pattern, group: usize, state: &mut State<'t>, pos: usize, next: Next<'_, 't>) -> bool {
+        // Past the limit every attempt fails, which unwinds the whole search
+        state.steps += 1;
+        if state.exceeded() {
+            return false;
+        }
         match pattern {
-            Pattern::ExactChar(c) => data.starts_with(*c),
-            Pattern::AnyChar => !data.is_empty(),
-            Pattern::AlphaNumeric => data.chars().next().map_or(false, |c| c.is_alphanumeric() || c == '_'),
-            Pattern::Sequence(patterns) => {
-                let mut remaining = data;
-                for p in patterns {
-                    if let Some(new_remaining) = Self::consume_match(p, remaining, captured_groups) {
-                        remaining = new_remaining;
-                    } else {
-                        return false;
-                    }
-                }
-                true
-            },
+            Pattern::Sequence(patterns) => Self::match_sequence(patterns, group, state, pos, next),
             Pattern::Repeated { min, max, pattern } => {
-                let mut count = 0;
-                let mut remaining = data;
-                while max.map_or(true, |m| count < m) {
-                    if let Some(new_remaining) = Self::consume_match(pattern, remaining, captured_groups) {
-                        remaining = new_remaining;
-                        count += 1;
-                    } else {
-                        break;
+                Self::match_repeat(pattern, group, *min, *max, state, pos, next)
+            }
+            Pattern::OneOrMore(pattern) => Self::match_repeat(pattern, group, 1, None, state, pos, next),
+            Pattern::ZeroOrOne(pattern) => Self::match_repeat(pattern, group, 0, Some(1), state, pos, next),
+            Pattern::OneOf(patterns) | Pattern::Alternation(patterns) => Self::match_choice(patterns, group, state, pos, next),
+            Pattern::CaptureGroup(pattern) | Pattern::NestedCapture(pattern) | Pattern::NamedCapture { pattern, .. } => {
+                Self::match_capture(group, state, pos, next, &mut |state, pos, next| {
+                    Self::match_here(pattern, group + 1, state, pos, next)
+                })
+            }
+            Pattern::Literal(literal) => {
+                let mut end = pos;
+                for c in literal.chars() {
+                    match Self::match_char(&Pattern::ExactChar(c), state, end) {
+                        Some(next_end) => end = next_end,
+                        None => return false,
                     }
                 }
-                count >= *min
-            },
-            Pattern::OneOf(patterns) => patterns.iter().any(|p| Self::match_from_start(p, data, captured_groups)),
-            Pattern::CharacterSet { chars, negated } => {
-                data.chars().next().map_or(false, |c| chars.contains(c) != *negated)
-            },
-            Pattern::StartOfLine => data.lines().next() == Some(data),
-            Pattern::EndOfLine => data.is_empty(),
-            Pattern::OneOrMore(pattern) => {
-                let mut count = 0;
-                let mut remaining = data;
-                while let Some(new_remaining) = Self::consume_match(pattern, remaining, captured_groups) {
-                    remaining = new_remaining;
-                    count += 1;
-                }
-                count > 0
-            },
-            Pattern::ZeroOrOne(pattern) => {
-                Self::match_from_start(pattern, data, captured_groups) || true
-            },
-            Pattern::Alternation(patterns) => patterns.iter().any(|p| Self::match_from_start(p, data, captured_groups)),
+                next(state, end)
+            }
+            Pattern::StartOfLine => {
+                let at_start = pos == 0 || state.text[..pos].chars().next_back() == state.config.line_terminator;
+                at_start && next(state, pos)
+            }
+            Pattern::EndOfLine => state.at_line_end(pos) && next(state, pos),
+            Pattern::NoWordBefore => {
+                !state.text[..pos].chars().next_back().is_some_and(is_word_char) && next(state, pos)
+            }
+            Pattern::NoWordAfter => {
+                !state.text[pos..].chars().next().is_some_and(is_word_char) && next(state, pos)
+            }
             Pattern::Backreference(n) => {
-                let index = n - 1;
-                captured_groups.get(index).map_or(false, |group| data.starts_with(group))
-            },
-            Pattern::CaptureGroup(pattern) | Pattern::NestedCapture(pattern) => {
-                let start_len = captured_groups.len();
-                let result = Self::match_from_start(pattern, data, captured_groups);
-                if result {
-                    let length = Self::match_length(pattern, data, captured_groups);
-                    let captured = data[..length].to_string();
-                    captured_groups.push(captured);
-                } else {
-                    captured_groups.truncate(start_len);
+                let captured = n
+                    .checked_sub(1)
+                    .and_then(|index| state.slots.get(index).copied().flatten());
+                match captured {
+                    Some((start, end)) => {
+                        let len = end - start;
+                        state.text[pos..].starts_with(&state.text[start..end]) && next(state, pos + len)
+                    }
+                    None => false,
                 }
-                result
+            }
+            _ => match Self::match_char(pattern, state, pos) {
+                Some(end) => next(state, end),
+                None => false,
             },
         }
     }
 
-    fn match_from_end(pattern: &Pattern, data: &str) -> bool {
-        match pattern {
-            Pattern::EndOfLine => data.is_empty(),
+    // Match a pattern that consumes exactly one character, returning the position after it
+    fn match_char(pattern: &Pattern, state: &State, pos: usize) -> Option<usize> {
+        let c = state.text[pos..].chars().next()?;
+        let ends_line = Some(c) == state.config.line_terminator || (c == '\r' && state.at_line_end(pos));
+        if ends_line && (!state.config.multiline || matches!(pattern, Pattern::AnyChar)) {
+            return None;
+        }
+        let matched = match pattern {
+            Pattern::ExactChar(expected) => c == *expected,
+            Pattern::AnyChar => true,
+            Pattern::AlphaNumeric => is_word_char(c),
+            Pattern::CharacterSet { chars, negated } => chars.contains(c) != *negated,
             _ => false,
+        };
+        matched.then_some(pos + c.len_utf8())
+    }
+
+    fn is_single_char(pattern: &Pattern) -> bool {
+        matches!(
+            pattern,
+            Pattern::ExactChar(_) | Pattern::AnyChar | Pattern::AlphaNumeric | Pattern::CharacterSet { .. }
+        )
+    }
+
+    fn match_sequence<'t>(patterns: &[Pattern], group: usize, state: &mut State<'t>, pos: usize, next: Next<'_, 't>) -> bool {
+        match patterns.split_first() {
+            None => next(state, pos),
+            Some((first, rest)) => {
+                let rest_group = group + first.group_count();
+                Self::match_here(first, group, state, pos, &mut |state, pos| {
+                    Self::match_sequence(rest, rest_group, state, pos, next)
+                })
+            }
         }
     }
 
-    fn consume_match<'a>(pattern: &Pattern, data: &'a str, captured_groups: &mut Vec<String>) -> Option<&'a str> {
-        let start_len = captured_groups.len();
-        let length = Self::match_length(pattern, data, captured_groups);
-        if length > 0 {
-            Some(&data[length..])
-        } else {
-            captured_groups.truncate(start_len);
-            None
+    fn match_choice<'t>(patterns: &[Pattern], group: usize, state: &mut State<'t>, pos: usize, next: Next<'_, 't>) -> bool {
+        let mut group = group;
+        for pattern in patterns {
+            if Self::match_here(pattern, group, state, pos, next) {
+                return true;
+            }
+            group += pattern.group_count();
         }
+        false
     }
 
-    fn match_length(pattern: &Pattern, data: &str, captured_groups: &mut Vec<String>) -> usize {
-        match pattern {
-            Pattern::ExactChar(c) => if data.starts_with(*c) { 1 } else { 0 },
-            Pattern::AnyChar => if !data.is_empty() { 1 } else { 0 },
-            Pattern::AlphaNumeric => if data.chars().next().map_or(false, |c| c.is_alphanumeric() || c == '_') { 1 } else { 0 },
-            Pattern::Sequence(patterns) => {
-                let mut length = 0;
-                let mut remaining = data;
-                for pattern in patterns {
-                    if let Some(new_remaining) = Self::consume_match(pattern, remaining, captured_groups) {
-                        length += remaining.len() - new_remaining.len();
-                        remaining = new_remaining;
-                    } else {
-                        return 0;
-                    }
-                }
-                length
-            },
-            Pattern::Repeated { min, max, pattern } => {
-                let mut count = 0;
-                let mut length = 0;
-                let mut remaining = data;
-                while max.map_or(true, |m| count < m) {
-                    if let Some(new_remaining) = Self::consume_match(pattern, remaining, captured_groups) {
-                        length += remaining.len() - new_remaining.len();
-                        remaining = new_remaining;
-                        count += 1;
-                    } else {
-                        break;
-                    }
-                }
-                if count >= *min { length } else { 0 }
-            },
-            Pattern::OneOf(patterns) => patterns
-                .iter()
-                .map(|p| Self::match_length(p, data, captured_groups))
-                .max()
-                .unwrap_or(0),
-            Pattern::CharacterSet { chars, negated } => {
-                if data.chars().next().map_or(false, |c| chars.contains(c) != *negated) { 1 } else { 0 }
-            },
-            Pattern::StartOfLine => 0,
-            Pattern::EndOfLine => 0,
-            Pattern::OneOrMore(pattern) => {
-                let mut length = 0;
-                let mut remaining = data;
-                while let Some(new_remaining) = Self::consume_match(pattern, remaining, captured_groups) {
-                    length += remaining.len() - new_remaining.len();
-                    remaining = new_remaining;
-                }
-                length
-            },
-            Pattern::ZeroOrOne(pattern) => {
-                Self::consume_match(pattern, data, captured_groups)
-                    .map(|r| data.len() - r.len())
-                    .unwrap_or(0)
-            },
-            Pattern::Alternation(patterns) => patterns
-                .iter()
-                .map(|p| Self::match_length(p, data, captured_groups))
-                .max()
-                .unwrap_or(0),
-            Pattern::Backreference(n) => {
-                let index = n - 1;
-                captured_groups.get(index)
-                    .filter(|&group| data.starts_with(group))
-                    .map(|group| group.len())
-                    .unwrap_or(0)
-            },
-            Pattern::CaptureGroup(pattern) | Pattern::NestedCapture(pattern) => {
-                let start_len = captured_groups.len();
-                let length = Self::match_length(pattern, data, captured_groups);
-                if length > 0 {
-                    let captured = data[..length].to_string();
-                    captured_groups.push(captured);
-                    length
-                } else {
-                    captured_groups.truncate(start_len);
-                    0
+    // Record the span matched by `inner` as capture group `group`
+    fn match_capture<'t>(
+        group: usize,
+        state: &mut State<'t>,
+        pos: usize,
+        next: Next<'_, 't>,
+        inner: &mut dyn FnMut(&mut State<'t>, usize, Next<'_, 't>) -> bool,
+    ) -> bool {
+        let start = pos;
+        inner(state, pos, &mut |state, end| {
+            let saved = state.slots[group];
+            state.slots[group] = Some((start, end));
+            if next(state, end) {
+                true
+            } else {
+                state.slots[group] = saved;
+                false
+            }
+        })
+    }
+
+    // Greedy repetition that backtracks one iteration at a time
+    fn match_repeat<'t>(
+        pattern: &Pattern,
+        group: usize,
+        min: usize,
+        max: Option<usize>,
+        state: &mut State<'t>,
+        pos: usize,
+        next: Next<'_, 't>,
+    ) -> bool {
+        if Self::is_single_char(pattern) {
+            return Self::match_repeat_char(pattern, min, max, state, pos, next);
+        }
+        Self::match_repeat_from(pattern, group, min, max, 0, state, pos, next)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn match_repeat_from<'t>(
+        pattern: &Pattern,
+        group: usize,
+        min: usize,
+        max: Option<usize>,
+        count: usize,
+        state: &mut State<'t>,
+        pos: usize,
+        next: Next<'_, 't>,
+    ) -> bool {
+        if max.map_or(true, |max| count < max) {
+            let matched = Self::match_here(pattern, group, state, pos, &mut |state, end| {
+                // An empty iteration can't make progress once the minimum is met
+                if end == pos && count >= min {
+                    return false;
                 }
-            },
+                Self::match_repeat_from(pattern, group, min, max, count + 1, state, end, next)
+            });
+            if matched {
+                return true;
+            }
+        }
+        count >= min && next(state, pos)
+    }
+
+    // Repetition of a single character, matched without recursing per iteration
+    fn match_repeat_char<'t>(
+        pattern: &Pattern,
+        min: usize,
+        max: Option<usize>,
+        state: &mut State<'t>,
+        pos: usize,
+        next: Next<'_, 't>,
+    ) -> bool {
+        let mut ends = vec![pos];
+        while max.map_or(true, |max| ends.len() <= max) {
+            match Self::match_char(pattern, state, *ends.last().unwrap()) {
+                Some(end) => ends.push(end),
+                None => break,
+            }
+        }
+        ends.iter()
+            .enumerate()
+            .rev()
+            .take_while(|&(count, _)| count >= min)
+            .any(|(_, &end)| next(state, end))
+    }
+}
+
+impl State<'_> {
+    fn exceeded(&self) -> bool {
+        self.backtrack_limit.is_some_and(|limit| self.steps > limit)
+    }
+
+    // Whether a line ending, or the end of the text, starts at `pos`
+    fn at_line_end(&self, pos: usize) -> bool {
+        let rest = &self.text[pos..];
+        let rest = match rest.strip_prefix('\r') {
+            Some(after_cr) if self.config.crlf => after_cr,
+            _ => rest,
+        };
+        rest.is_empty() || rest.chars().next() == self.config.line_terminator
+    }
+}
+
+pub(crate) fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl Captures {
+    // Span of a capture group, or of the whole match for group 0.
+    // None if the group doesn't exist or didn't take part in the match.
+    pub fn get(&self, group: usize) -> Option<Range<usize>> {
+        self.spans.get(group).cloned().flatten()
+    }
+
+    // Number of groups, counting the whole match
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    // Always false, as the whole match is a group
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+}
+
+impl Iterator for CapturesIter<'_, '_> {
+    type Item = Captures;
+
+    fn next(&mut self) -> Option<Captures> {
+        while self.pos <= self.text.len() {
+            let captures = Matcher::captures_with(self.pattern, self.text, self.pos, MatchConfig::default())?;
+            let found = captures.get(0)?;
+            if found.is_empty() && self.last_end == Some(found.end) {
+                // Skip an empty match right after the previous match
+                self.pos = found.start + self.text[found.start..].chars().next().map_or(1, char::len_utf8);
+                continue;
+            }
+            self.pos = found.end;
+            self.last_end = Some(found.end);
+            return Some(captures);
         }
+        None
+    }
+}
+
+impl Iterator for FindIter<'_, '_> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Range<usize>> {
+        self.0.next().and_then(|captures| captures.get(0))
     }
-}
\ No newline at end of file
+}
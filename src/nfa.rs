@@ -0,0 +1,198 @@
+use crate::matcher::is_word_char;
+use crate::Pattern;
+
+// One step of a compiled program
+#[derive(Clone, Debug)]
+enum Inst {
+    // Consume one character matched by a single-character pattern
+    Char(Pattern),
+    // Continue at both targets
+    Split(usize, usize),
+    Jump(usize),
+    // Continue only where a zero-width assertion holds
+    Assert(Pattern),
+    // The pattern with this index matched
+    Match(usize),
+}
+
+// Several patterns compiled into a single NFA, which finds every pattern that matches
+// in one pass over the text. Capture groups only group, so backreferences can't be compiled.
+#[derive(Clone, Debug)]
+pub(crate) struct Program {
+    insts: Vec<Inst>,
+    // Where each pattern starts, indexed by pattern
+    starts: Vec<usize>,
+}
+
+// Instructions reached at one position, each at most once
+struct Threads {
+    pcs: Vec<usize>,
+    seen: Vec<bool>,
+}
+
+impl Program {
+    // Compile patterns that have no backreferences
+    pub(crate) fn new(patterns: &[Pattern]) -> Program {
+        let mut program = Program { insts: vec![], starts: vec![] };
+        for (index, pattern) in patterns.iter().enumerate() {
+            program.starts.push(program.insts.len());
+            program.compile(pattern);
+            program.insts.push(Inst::Match(index));
+        }
+        program
+    }
+
+    fn compile(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::ExactChar(_) | Pattern::AnyChar | Pattern::AlphaNumeric | Pattern::CharacterSet { .. } => {
+                self.insts.push(Inst::Char(pattern.clone()));
+            }
+            Pattern::Literal(literal) => {
+                self.insts.extend(literal.chars().map(|c| Inst::Char(Pattern::ExactChar(c))));
+            }
+            Pattern::StartOfLine | Pattern::EndOfLine | Pattern::NoWordBefore | Pattern::NoWordAfter => {
+                self.insts.push(Inst::Assert(pattern.clone()));
+            }
+            Pattern::Sequence(parts) => parts.iter().for_each(|part| self.compile(part)),
+            Pattern::OneOf(branches) | Pattern::Alternation(branches) => {
+                let mut jumps = vec![];
+                for (i, branch) in branches.iter().enumerate() {
+                    if i + 1 == branches.len() {
+                        self.compile(branch);
+                        break;
+                    }
+                    let split = self.insts.len();
+                    self.insts.push(Inst::Split(split + 1, 0));
+                    self.compile(branch);
+                    jumps.push(self.insts.len());
+                    self.insts.push(Inst::Jump(0));
+                    self.insts[split] = Inst::Split(split + 1, self.insts.len());
+                }
+                let end = self.insts.len();
+                for jump in jumps {
+                    self.insts[jump] = Inst::Jump(end);
+                }
+            }
+            Pattern::Repeated { min, max, pattern } => self.compile_repeat(pattern, *min, *max),
+            Pattern::OneOrMore(pattern) => self.compile_repeat(pattern, 1, None),
+            Pattern::ZeroOrOne(pattern) => self.compile_repeat(pattern, 0, Some(1)),
+            Pattern::CaptureGroup(pattern) | Pattern::NestedCapture(pattern) | Pattern::NamedCapture { pattern, .. } => {
+                self.compile(pattern);
+            }
+            Pattern::Backreference(_) => unreachable!("backreferences can't be compiled"),
+        }
+    }
+
+    // The required iterations, then a loop or as many optional ones as allowed
+    fn compile_repeat(&mut self, pattern: &Pattern, min: usize, max: Option<usize>) {
+        for _ in 0..min {
+            self.compile(pattern);
+        }
+        match max {
+            None => {
+                let split = self.insts.len();
+                self.insts.push(Inst::Split(split + 1, 0));
+                self.compile(pattern);
+                self.insts.push(Inst::Jump(split));
+                self.insts[split] = Inst::Split(split + 1, self.insts.len());
+            }
+            Some(max) => {
+                for _ in min..max {
+                    let split = self.insts.len();
+                    self.insts.push(Inst::Split(split + 1, 0));
+                    self.compile(pattern);
+                    self.insts[split] = Inst::Split(split + 1, self.insts.len());
+                }
+            }
+        }
+    }
+
+    // Which patterns match anywhere in the text, taken as a single line
+    pub(crate) fn matching(&self, text: &str) -> Vec<bool> {
+        let mut matched = vec![false; self.starts.len()];
+        let mut current = Threads::new(self.insts.len());
+        let mut next = Threads::new(self.insts.len());
+        let positions = text.char_indices().map(|(pos, c)| (pos, Some(c))).chain(Some((text.len(), None)));
+        for (pos, c) in positions {
+            // A match may start at any position
+            for (index, &start) in self.starts.iter().enumerate() {
+                if !matched[index] {
+                    self.add(&mut current, start, text, pos, &mut matched);
+                }
+            }
+            let Some(c) = c else { break };
+            if matched.iter().all(|&m| m) {
+                break;
+            }
+            for &pc in &current.pcs {
+                if let Inst::Char(pattern) = &self.insts[pc] {
+                    if char_matches(pattern, c) {
+                        self.add(&mut next, pc + 1, text, pos + c.len_utf8(), &mut matched);
+                    }
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+            next.clear();
+        }
+        matched
+    }
+
+    // Add an instruction and everything reachable from it without consuming a character
+    fn add(&self, threads: &mut Threads, pc: usize, text: &str, pos: usize, matched: &mut [bool]) {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if threads.seen[pc] {
+                continue;
+            }
+            threads.seen[pc] = true;
+            threads.pcs.push(pc);
+            match &self.insts[pc] {
+                Inst::Char(_) => {}
+                Inst::Split(first, second) => stack.extend([*second, *first]),
+                Inst::Jump(target) => stack.push(*target),
+                Inst::Assert(assertion) => {
+                    if assertion_holds(assertion, text, pos) {
+                        stack.push(pc + 1);
+                    }
+                }
+                Inst::Match(index) => matched[*index] = true,
+            }
+        }
+    }
+}
+
+impl Threads {
+    fn new(len: usize) -> Threads {
+        Threads {
+            pcs: vec![],
+            seen: vec![false; len],
+        }
+    }
+
+    fn clear(&mut self) {
+        for &pc in &self.pcs {
+            self.seen[pc] = false;
+        }
+        self.pcs.clear();
+    }
+}
+
+fn char_matches(pattern: &Pattern, c: char) -> bool {
+    match pattern {
+        Pattern::ExactChar(expected) => c == *expected,
+        Pattern::AnyChar => true,
+        Pattern::AlphaNumeric => is_word_char(c),
+        Pattern::CharacterSet { chars, negated } => chars.contains(c) != *negated,
+        _ => false,
+    }
+}
+
+fn assertion_holds(assertion: &Pattern, text: &str, pos: usize) -> bool {
+    match assertion {
+        Pattern::StartOfLine => pos == 0,
+        Pattern::EndOfLine => pos == text.len(),
+        Pattern::NoWordBefore => !text[..pos].chars().next_back().is_some_and(is_word_char),
+        Pattern::NoWordAfter => !text[pos..].chars().next().is_some_and(is_word_char),
+        _ => false,
+    }
+}
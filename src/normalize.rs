@@ -0,0 +1,218 @@
+use crate::pattern::set_chars;
+use crate::Pattern;
+
+impl Pattern {
+    // The canonical form of a pattern, which matches the same text with the same groups.
+    // Every repetition is `Repeated`, every group `CaptureGroup` or `NamedCapture` and every
+    // choice `OneOf`, nested sequences and repetitions are flattened, alternatives sharing a
+    // prefix are factored, single-character choices become sets and runs of characters
+    // become literals.
+    pub fn normalize(self) -> Pattern {
+        merge_literals(simplify(self))
+    }
+}
+
+// Canonical variants and flattened structure, still one node per character
+fn simplify(pattern: Pattern) -> Pattern {
+    match pattern {
+        Pattern::Sequence(parts) => sequence(parts.into_iter().map(simplify).collect()),
+        Pattern::Repeated { min, max, pattern } => repeat(min, max, simplify(*pattern)),
+        Pattern::OneOrMore(pattern) => repeat(1, None, simplify(*pattern)),
+        Pattern::ZeroOrOne(pattern) => repeat(0, Some(1), simplify(*pattern)),
+        Pattern::OneOf(branches) | Pattern::Alternation(branches) => one_of(branches.into_iter().map(simplify).collect()),
+        Pattern::CaptureGroup(pattern) | Pattern::NestedCapture(pattern) => Pattern::CaptureGroup(Box::new(simplify(*pattern))),
+        Pattern::NamedCapture { name, pattern } => Pattern::NamedCapture {
+            name,
+            pattern: Box::new(simplify(*pattern)),
+        },
+        Pattern::Literal(literal) => sequence(literal.chars().map(Pattern::ExactChar).collect()),
+        pattern => pattern,
+    }
+}
+
+// The parts of a sequence, with nested sequences spliced in
+fn sequence(parts: Vec<Pattern>) -> Pattern {
+    let mut flat = Vec::with_capacity(parts.len());
+    for part in parts {
+        match part {
+            Pattern::Sequence(inner) => flat.extend(inner),
+            part => flat.push(part),
+        }
+    }
+    if flat.len() == 1 {
+        flat.pop().unwrap()
+    } else {
+        Pattern::Sequence(flat)
+    }
+}
+
+// A repetition, folded into a repeated operand when that matches exactly the same counts
+fn repeat(min: usize, max: Option<usize>, pattern: Pattern) -> Pattern {
+    if (min, max) == (1, Some(1)) {
+        return pattern;
+    }
+    match pattern {
+        Pattern::Repeated {
+            min: inner_min,
+            max: inner_max,
+            pattern: inner,
+        } if is_fixed_width(&inner) && (max == Some(min) || inner_min <= 1) => {
+            // Counts from each outer iteration join up when the outer count is fixed or
+            // when the inner count can step by one
+            let max = match (max, inner_max) {
+                (Some(max), Some(inner_max)) => Some(max * inner_max),
+                (_, Some(0)) | (Some(0), _) => Some(0),
+                _ => None,
+            };
+            repeat(min * inner_min, max, *inner)
+        }
+        pattern => Pattern::Repeated {
+            min,
+            max,
+            pattern: Box::new(pattern),
+        },
+    }
+}
+
+// Whether the pattern always matches the same number of characters and has no groups,
+// so that nesting its repetitions can't change which match is found
+fn is_fixed_width(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::ExactChar(_) | Pattern::AnyChar | Pattern::AlphaNumeric | Pattern::CharacterSet { .. } => true,
+        Pattern::Literal(_) => true,
+        Pattern::Sequence(parts) => parts.iter().all(is_fixed_width),
+        _ => false,
+    }
+}
+
+// A choice between branches, tried in order
+fn one_of(branches: Vec<Pattern>) -> Pattern {
+    let mut flat: Vec<Pattern> = Vec::with_capacity(branches.len());
+    for branch in branches {
+        let inner = match branch {
+            Pattern::OneOf(inner) | Pattern::Alternation(inner) => inner,
+            branch => vec![branch],
+        };
+        for branch in inner {
+            // A repeated branch can't match where it failed before, unless its groups differ
+            if branch.has_capture_group() || !flat.contains(&branch) {
+                flat.push(branch);
+            }
+        }
+    }
+    let mut branches = factor_prefixes(flat);
+    if branches.len() == 1 {
+        return branches.pop().unwrap();
+    }
+
+    // Only one branch of a choice between single characters can match at a position
+    let mut chars = String::new();
+    for branch in &branches {
+        let branch_chars = match branch {
+            Pattern::ExactChar(c) => c.to_string(),
+            Pattern::CharacterSet { chars, negated: false } => chars.clone(),
+            _ => return Pattern::OneOf(branches),
+        };
+        for c in branch_chars.chars() {
+            if !chars.contains(c) {
+                chars.push(c);
+            }
+        }
+    }
+    // Unless the set can't be written as one, such as one holding `]`
+    match set_chars(&chars, false) {
+        Some(chars) => Pattern::CharacterSet { chars, negated: false },
+        None => Pattern::OneOf(branches),
+    }
+}
+
+// Replace each run of neighbouring branches that start alike with their common
+// prefix followed by a choice between the rest, which keeps the order they are tried in
+fn factor_prefixes(branches: Vec<Pattern>) -> Vec<Pattern> {
+    fn parts(branch: &Pattern) -> &[Pattern] {
+        match branch {
+            Pattern::Sequence(parts) => parts,
+            branch => std::slice::from_ref(branch),
+        }
+    }
+
+    let mut factored = Vec::with_capacity(branches.len());
+    let mut i = 0;
+    while i < branches.len() {
+        // Only a prefix that matches the same way in every branch can be tried once for all of them
+        let first = parts(&branches[i]).first().filter(|part| is_fixed_width(part));
+        let run = branches[i..]
+            .iter()
+            .take_while(|branch| first.is_some() && parts(branch).first() == first)
+            .count()
+            .max(1);
+        if run == 1 {
+            factored.push(branches[i].clone());
+            i += 1;
+            continue;
+        }
+
+        // The run shares at least its first part, and maybe more
+        let run_branches = &branches[i..i + run];
+        let first_parts = parts(&run_branches[0]);
+        let mut prefix_len = 1;
+        while prefix_len < first_parts.len()
+            && is_fixed_width(&first_parts[prefix_len])
+            && run_branches.iter().all(|branch| parts(branch).get(prefix_len) == Some(&first_parts[prefix_len]))
+        {
+            prefix_len += 1;
+        }
+        let prefix = first_parts[..prefix_len].to_vec();
+        let rests = run_branches
+            .iter()
+            .map(|branch| sequence(parts(branch)[prefix_len..].to_vec()))
+            .collect();
+        let mut factored_branch = prefix;
+        factored_branch.push(one_of(rests));
+        factored.push(sequence(factored_branch));
+        i += run;
+    }
+    factored
+}
+
+// Runs of two or more characters in sequences become literals
+fn merge_literals(pattern: Pattern) -> Pattern {
+    let boxed = |pattern: Box<Pattern>| Box::new(merge_literals(*pattern));
+    match pattern {
+        Pattern::Sequence(parts) => {
+            let mut merged = Vec::with_capacity(parts.len());
+            let mut run = String::new();
+            for part in parts.into_iter().map(merge_literals) {
+                match part {
+                    Pattern::ExactChar(c) => run.push(c),
+                    part => {
+                        push_run(&mut merged, &mut run);
+                        merged.push(part);
+                    }
+                }
+            }
+            push_run(&mut merged, &mut run);
+            if merged.len() == 1 {
+                merged.pop().unwrap()
+            } else {
+                Pattern::Sequence(merged)
+            }
+        }
+        Pattern::Repeated { min, max, pattern } => Pattern::Repeated { min, max, pattern: boxed(pattern) },
+        Pattern::OneOf(branches) => Pattern::OneOf(branches.into_iter().map(merge_literals).collect()),
+        Pattern::CaptureGroup(pattern) => Pattern::CaptureGroup(boxed(pattern)),
+        Pattern::NamedCapture { name, pattern } => Pattern::NamedCapture { name, pattern: boxed(pattern) },
+        pattern => pattern,
+    }
+}
+
+// Add a run of characters to a sequence as a single character or a literal
+fn push_run(parts: &mut Vec<Pattern>, run: &mut String) {
+    let mut chars = run.chars();
+    match (chars.next(), chars.next()) {
+        (None, _) => {}
+        (Some(c), None) => parts.push(Pattern::ExactChar(c)),
+        _ => parts.push(Pattern::Literal(run.clone())),
+    }
+    run.clear();
+}
@@ -0,0 +1,426 @@
+use crate::color::ColorChoice;
+
+pub const USAGE: &str = "Usage: grep [OPTION]... PATTERNS [FILE]...";
+
+pub const HELP: &str = "\
+Search for PATTERNS in each FILE.
+When FILE is '-' or not given, read standard input.
+
+Pattern selection and interpretation:
+  -E, --extended-regexp     PATTERNS are extended regular expressions
+  -F, --fixed-strings       PATTERNS are strings
+  -e, --regexp=PATTERNS     use PATTERNS for matching
+  -f, --file=FILE           take PATTERNS from FILE
+  -w, --word-regexp         match only whole words
+  -x, --line-regexp         match only whole lines
+  -z, --null-data           a data line ends in 0 byte, not newline
+      --crlf                treat CRLF as a line ending and strip CR from output
+  -U, --multiline           let matches span lines; '.' matches newline with (?s)
+      --dump-ast=FORMAT     print the parsed pattern and exit; FORMAT is 'json'
+
+Miscellaneous:
+  -v, --invert-match        select non-matching lines
+  -a, --text                equivalent to --binary-files=text
+  -I                        equivalent to --binary-files=without-match
+      --binary-files=TYPE   assume that binary files are TYPE;
+                            TYPE is 'binary', 'text', or 'without-match'
+  -V, --version             display version information and exit
+      --help                display this help text and exit
+
+Output control:
+  -m, --max-count=NUM       stop after NUM selected lines
+  -b, --byte-offset         print the byte offset with output lines
+  -n, --line-number         print line number with output lines
+      --column              print the column of the first match
+      --vimgrep             print one file:line:column:text line per match
+      --json                print results as JSON Lines messages like ripgrep
+  -H, --with-filename       print file name with output lines
+  -h, --no-filename         suppress the file name prefix on output
+  -Z, --null                print 0 byte after FILE name
+  -o, --only-matching       show only nonempty parts of lines that match
+  -r, --replace=TEMPLATE    print TEMPLATE in place of each match, where
+                            $1 or ${name} stands for a capture group
+  -q, --quiet, --silent     suppress all normal output
+  -L, --files-without-match  print only names of FILEs with no selected lines
+  -l, --files-with-matches  print only names of FILEs with selected lines
+  -c, --count               print only a count of selected lines per FILE
+
+Context control:
+  -B, --before-context=NUM  print NUM lines of leading context
+  -A, --after-context=NUM   print NUM lines of trailing context
+  -C, --context=NUM         print NUM lines of output context
+  -NUM                      same as --context=NUM
+      --group-separator=SEP  print SEP between groups of context lines
+      --no-group-separator  don't print a separator between groups
+      --color[=WHEN],
+      --colour[=WHEN]       use markers to highlight the matching strings;
+                            WHEN is 'always', 'never', or 'auto'
+
+File search strategy:
+      --mmap                search files through memory maps
+      --no-mmap             always read files as streams
+
+Exit status is 0 if any line is selected, 1 otherwise;
+if any error occurs and -q is not given, the exit status is 2.";
+
+// A command line error, reported along with a usage hint
+#[derive(Debug, thiserror::Error)]
+#[error("{0}\n{USAGE}\nTry 'grep --help' for more information.")]
+pub struct UsageError(String);
+
+// What gets printed for each input
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputMode {
+    Lines,
+    Count,
+    FilesWithMatches,
+    FilesWithoutMatch,
+    Quiet,
+}
+
+// How inputs that look binary are searched
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryFiles {
+    // Report a match with a single message instead of printing lines
+    Binary,
+    Text,
+    WithoutMatch,
+}
+
+// How --dump-ast prints the pattern
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AstFormat {
+    Json,
+}
+
+impl std::str::FromStr for AstFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(AstFormat::Json),
+            _ => Err(format!("invalid argument {:?} for '--dump-ast'", s)),
+        }
+    }
+}
+
+impl std::str::FromStr for BinaryFiles {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "binary" => Ok(BinaryFiles::Binary),
+            "text" => Ok(BinaryFiles::Text),
+            "without-match" => Ok(BinaryFiles::WithoutMatch),
+            _ => Err(format!("invalid argument {:?} for '--binary-files'", s)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Options {
+    // Patterns from -e or the first operand, and files of patterns from -f
+    pub patterns: Vec<String>,
+    pub pattern_files: Vec<String>,
+    pub files: Vec<String>,
+    pub output: OutputMode,
+    pub only_matching: bool,
+    // Select the lines that do not match
+    pub invert_match: bool,
+    // Patterns are plain text rather than regexes
+    pub fixed_strings: bool,
+    pub word_regexp: bool,
+    pub line_regexp: bool,
+    pub max_count: Option<usize>,
+    pub line_number: bool,
+    pub byte_offset: bool,
+    pub column: bool,
+    // One output line per match, as `file:line:column:text`
+    pub vimgrep: bool,
+    pub json: bool,
+    // Template printed in place of each match
+    pub replace: Option<String>,
+    // Set by -H and -h, otherwise decided by the number of files
+    pub with_filename: Option<bool>,
+    pub before_context: usize,
+    pub after_context: usize,
+    // Printed between non-adjacent groups of context, if any
+    pub group_separator: Option<String>,
+    pub color: ColorChoice,
+    // Set by --mmap and --no-mmap, otherwise decided by file size
+    pub mmap: Option<bool>,
+    pub binary_files: BinaryFiles,
+    // Lines end with NUL instead of newline
+    pub null_data: bool,
+    // Print NUL after file names
+    pub null: bool,
+    // A carriage return before the line terminator is part of the line ending
+    pub crlf: bool,
+    // Match the pattern across lines
+    pub multiline: bool,
+    // Print the pattern's syntax tree instead of searching
+    pub dump_ast: Option<AstFormat>,
+    pub show_help: bool,
+    pub show_version: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Value {
+    None,
+    Required,
+    // Only given inline, as `--long=value`
+    Optional,
+    // A short alias for the long option with this value
+    Fixed(&'static str),
+}
+
+struct OptionSpec {
+    short: Option<char>,
+    long: &'static str,
+    value: Value,
+}
+
+const fn spec(short: Option<char>, long: &'static str, value: Value) -> OptionSpec {
+    OptionSpec { short, long, value }
+}
+
+const OPTIONS: &[OptionSpec] = &[
+    spec(Some('E'), "extended-regexp", Value::None),
+    spec(Some('e'), "regexp", Value::Required),
+    spec(Some('F'), "fixed-strings", Value::None),
+    spec(Some('f'), "file", Value::Required),
+    spec(Some('w'), "word-regexp", Value::None),
+    spec(Some('x'), "line-regexp", Value::None),
+    spec(Some('v'), "invert-match", Value::None),
+    spec(Some('V'), "version", Value::None),
+    spec(None, "help", Value::None),
+    spec(Some('m'), "max-count", Value::Required),
+    spec(Some('b'), "byte-offset", Value::None),
+    spec(Some('n'), "line-number", Value::None),
+    spec(None, "column", Value::None),
+    spec(None, "vimgrep", Value::None),
+    spec(None, "json", Value::None),
+    spec(Some('r'), "replace", Value::Required),
+    spec(Some('H'), "with-filename", Value::None),
+    spec(Some('h'), "no-filename", Value::None),
+    spec(Some('o'), "only-matching", Value::None),
+    spec(Some('q'), "quiet", Value::None),
+    spec(None, "silent", Value::None),
+    spec(Some('L'), "files-without-match", Value::None),
+    spec(Some('l'), "files-with-matches", Value::None),
+    spec(Some('c'), "count", Value::None),
+    spec(Some('B'), "before-context", Value::Required),
+    spec(Some('A'), "after-context", Value::Required),
+    spec(Some('C'), "context", Value::Required),
+    spec(None, "group-separator", Value::Required),
+    spec(None, "no-group-separator", Value::None),
+    spec(None, "color", Value::Optional),
+    spec(None, "colour", Value::Optional),
+    spec(None, "binary-files", Value::Required),
+    spec(Some('a'), "text", Value::None),
+    spec(Some('I'), "binary-files", Value::Fixed("without-match")),
+    spec(Some('z'), "null-data", Value::None),
+    spec(Some('Z'), "null", Value::None),
+    spec(None, "crlf", Value::None),
+    spec(Some('U'), "multiline", Value::None),
+    spec(None, "dump-ast", Value::Required),
+    spec(None, "mmap", Value::None),
+    spec(None, "no-mmap", Value::None),
+];
+
+// Context sizes as given, resolved once all options are seen
+#[derive(Default)]
+struct ContextArgs {
+    before: Option<usize>,
+    after: Option<usize>,
+    both: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            patterns: vec![],
+            pattern_files: vec![],
+            files: vec![],
+            output: OutputMode::Lines,
+            only_matching: false,
+            invert_match: false,
+            fixed_strings: false,
+            word_regexp: false,
+            line_regexp: false,
+            max_count: None,
+            line_number: false,
+            byte_offset: false,
+            column: false,
+            vimgrep: false,
+            json: false,
+            replace: None,
+            with_filename: None,
+            before_context: 0,
+            after_context: 0,
+            group_separator: Some("--".to_string()),
+            color: ColorChoice::Never,
+            mmap: None,
+            binary_files: BinaryFiles::Binary,
+            null_data: false,
+            null: false,
+            crlf: false,
+            multiline: false,
+            dump_ast: None,
+            show_help: false,
+            show_version: false,
+        }
+    }
+}
+
+impl Options {
+    // Parse command line arguments, not including the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, UsageError> {
+        let mut options = Options::default();
+        let mut context = ContextArgs::default();
+        let mut operands = vec![];
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                operands.extend(args.by_ref());
+                break;
+            }
+            if let Some(long) = arg.strip_prefix("--") {
+                let (name, inline) = match long.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (long, None),
+                };
+                let spec = OPTIONS
+                    .iter()
+                    .find(|spec| spec.long == name && !matches!(spec.value, Value::Fixed(_)))
+                    .ok_or_else(|| UsageError(format!("unrecognized option '--{}'", name)))?;
+                let value = match (spec.value, inline) {
+                    (Value::None, Some(_)) => {
+                        return Err(UsageError(format!("option '--{}' doesn't allow an argument", name)));
+                    }
+                    (Value::Required, None) => Some(
+                        args.next()
+                            .ok_or_else(|| UsageError(format!("option '--{}' requires an argument", name)))?,
+                    ),
+                    (_, inline) => inline,
+                };
+                options.apply(spec, value, &mut context)?;
+            } else if arg.len() > 1 && arg.starts_with('-') {
+                // A cluster of short options such as `-nA3` or `-5`
+                let cluster = &arg[1..];
+                let mut rest = cluster;
+                while let Some(c) = rest.chars().next() {
+                    if c.is_ascii_digit() {
+                        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+                        context.both = Some(parse_num("context", &rest[..digits])?);
+                        rest = &rest[digits..];
+                        continue;
+                    }
+                    rest = &rest[c.len_utf8()..];
+                    let spec = OPTIONS
+                        .iter()
+                        .find(|spec| spec.short == Some(c))
+                        .ok_or_else(|| UsageError(format!("invalid option -- '{}'", c)))?;
+                    if let Value::Fixed(value) = spec.value {
+                        options.apply(spec, Some(value.to_string()), &mut context)?;
+                        continue;
+                    }
+                    if spec.value == Value::Required {
+                        let value = if rest.is_empty() {
+                            args.next()
+                                .ok_or_else(|| UsageError(format!("option requires an argument -- '{}'", c)))?
+                        } else {
+                            rest.to_string()
+                        };
+                        options.apply(spec, Some(value), &mut context)?;
+                        break;
+                    }
+                    options.apply(spec, None, &mut context)?;
+                }
+            } else {
+                operands.push(arg);
+            }
+        }
+
+        if options.show_help || options.show_version {
+            return Ok(options);
+        }
+
+        // Without -e or -f the first operand is the pattern
+        let mut files = operands.into_iter();
+        if options.patterns.is_empty() && options.pattern_files.is_empty() {
+            options.patterns.push(files.next().ok_or_else(|| UsageError("no pattern given".to_string()))?);
+        }
+        options.files = files.collect();
+
+        // JSON messages describe selected lines, not counts or file lists
+        if options.json && !matches!(options.output, OutputMode::Lines | OutputMode::Quiet) {
+            return Err(UsageError("--json can't be combined with -c, -l or -L".to_string()));
+        }
+        if options.vimgrep {
+            options.line_number = true;
+            options.column = true;
+        }
+        // -A and -B take precedence over -C
+        options.before_context = context.before.or(context.both).unwrap_or(0);
+        options.after_context = context.after.or(context.both).unwrap_or(0);
+
+        Ok(options)
+    }
+
+    fn apply(&mut self, spec: &OptionSpec, value: Option<String>, context: &mut ContextArgs) -> Result<(), UsageError> {
+        let value = value.unwrap_or_default();
+        match spec.long {
+            // The last of -E and -F wins
+            "extended-regexp" => self.fixed_strings = false,
+            "regexp" => self.patterns.push(value),
+            "fixed-strings" => self.fixed_strings = true,
+            "file" => self.pattern_files.push(value),
+            "word-regexp" => self.word_regexp = true,
+            "line-regexp" => self.line_regexp = true,
+            "invert-match" => self.invert_match = true,
+            "version" => self.show_version = true,
+            "help" => self.show_help = true,
+            "max-count" => self.max_count = Some(parse_num(spec.long, &value)?),
+            "byte-offset" => self.byte_offset = true,
+            "line-number" => self.line_number = true,
+            "column" => self.column = true,
+            "vimgrep" => self.vimgrep = true,
+            "json" => self.json = true,
+            "replace" => self.replace = Some(value),
+            "with-filename" => self.with_filename = Some(true),
+            "no-filename" => self.with_filename = Some(false),
+            "only-matching" => self.only_matching = true,
+            "quiet" | "silent" => self.output = OutputMode::Quiet,
+            "files-without-match" => self.output = OutputMode::FilesWithoutMatch,
+            "files-with-matches" => self.output = OutputMode::FilesWithMatches,
+            "count" => self.output = OutputMode::Count,
+            "before-context" => context.before = Some(parse_num(spec.long, &value)?),
+            "after-context" => context.after = Some(parse_num(spec.long, &value)?),
+            "context" => context.both = Some(parse_num(spec.long, &value)?),
+            "group-separator" => self.group_separator = Some(value),
+            "no-group-separator" => self.group_separator = None,
+            "color" | "colour" if value.is_empty() => self.color = ColorChoice::Auto,
+            "color" | "colour" => self.color = value.parse().map_err(UsageError)?,
+            "binary-files" => self.binary_files = value.parse().map_err(UsageError)?,
+            "dump-ast" => self.dump_ast = Some(value.parse().map_err(UsageError)?),
+            "text" => self.binary_files = BinaryFiles::Text,
+            "null-data" => self.null_data = true,
+            "null" => self.null = true,
+            "crlf" => self.crlf = true,
+            "multiline" => self.multiline = true,
+            "mmap" => self.mmap = Some(true),
+            "no-mmap" => self.mmap = Some(false),
+            _ => unreachable!("option --{} has no handler", spec.long),
+        }
+        Ok(())
+    }
+}
+
+// Parse the numeric argument of an option
+fn parse_num(option: &str, value: &str) -> Result<usize, UsageError> {
+    value
+        .parse()
+        .map_err(|_| UsageError(format!("invalid argument {:?} for '--{}'", value, option)))
+}
@@ -1,3 +1,4 @@
+use crate::pattern::{DIGITS, SPACES};
 use crate::Pattern;
 use std::str::FromStr;
 
@@ -9,45 +10,154 @@ impl FromStr for Pattern {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // Helper function to parse groups and handle nested patterns
-        fn parse_group(s: &str, chars: &mut std::str::Chars, nested_level: usize) -> Result<Pattern, String> {
+        // Collapse the pieces parsed so far into a single branch of an alternation
+        fn push_branch(alternatives: &mut Vec<Pattern>, current: &mut Vec<Pattern>) {
+            if !current.is_empty() {
+                alternatives.push(if current.len() == 1 {
+                    current.pop().unwrap()
+                } else {
+                    Pattern::Sequence(std::mem::take(current))
+                });
+            }
+        }
+
+        // Parse the digits of a `{min,max}` repeat, consuming the closing brace
+        fn parse_repeat(chars: &mut std::str::Chars) -> Result<(usize, Option<usize>), String> {
+            let mut min_str = String::new();
+            let mut max_str = String::new();
+            let mut seen_comma = false;
+            for c in chars.by_ref() {
+                match c {
+                    '0'..='9' if seen_comma => max_str.push(c),
+                    '0'..='9' => min_str.push(c),
+                    ',' if !seen_comma => seen_comma = true,
+                    '}' => {
+                        let min = if min_str.is_empty() {
+                            0
+                        } else {
+                            min_str.parse().map_err(|_| "Invalid repeat count".to_string())?
+                        };
+                        let max = if !seen_comma {
+                            Some(min)
+                        } else if max_str.is_empty() {
+                            None
+                        } else {
+                            Some(max_str.parse().map_err(|_| "Invalid repeat count".to_string())?)
+                        };
+                        if max.is_some_and(|max| max < min) {
+                            return Err("Invalid repeat range".to_string());
+                        }
+                        return Ok((min, max));
+                    }
+                    _ => return Err("Invalid character in repeat".to_string()),
+                }
+            }
+            Err("Unterminated '{' repeat".to_string())
+        }
+
+        // Parse the flags of a `(?flags)` group, consuming the closing paren.
+        // Returns whether `.` matches newlines from then on.
+        fn parse_flags(s: &str, chars: &mut std::str::Chars, dot_all: bool) -> Result<bool, String> {
+            let mut flags = String::new();
+            for c in chars.by_ref() {
+                if c == ')' {
+                    return match flags.as_str() {
+                        "s" => Ok(true),
+                        "-s" => Ok(false),
+                        "" => Ok(dot_all),
+                        _ => Err(format!("Unsupported flags {:?} in {:?}", flags, s)),
+                    };
+                }
+                flags.push(c);
+            }
+            Err(format!("Unterminated '(' group in {:?}", s))
+        }
+
+        // Parse the name of a `(?<name>...)` group, consuming the closing angle bracket
+        fn parse_group_name(s: &str, chars: &mut std::str::Chars) -> Result<String, String> {
+            let mut name = String::new();
+            for c in chars.by_ref() {
+                match c {
+                    '>' if !name.is_empty() => return Ok(name),
+                    'a'..='z' | 'A'..='Z' | '_' => name.push(c),
+                    '0'..='9' if !name.is_empty() => name.push(c),
+                    _ => break,
+                }
+            }
+            Err(format!("Invalid group name in {:?}", s))
+        }
+
+        // Helper function to parse groups and handle nested patterns.
+        // Returns the contents of the group, with several branches as a `OneOf`, and
+        // leaves it to the caller whether they capture.
+        // Flags set inside a group last until the end of that group.
+        fn parse_group(s: &str, chars: &mut std::str::Chars, nested_level: usize, mut dot_all: bool) -> Result<Pattern, String> {
             let mut alternatives = vec![];
             let mut current = vec![];
 
             while let Some(c) = chars.next() {
                 match c {
+                    '(' if chars.clone().next() == Some('?') => {
+                        chars.next();
+                        if let Some(rest) = chars.as_str().strip_prefix(':') {
+                            // A non-capturing group is just its contents
+                            *chars = rest.chars();
+                            current.push(parse_group(s, chars, nested_level + 1, dot_all)?);
+                        } else if let Some(rest) = chars.as_str().strip_prefix("P<").or(chars.as_str().strip_prefix('<')) {
+                            *chars = rest.chars();
+                            let name = parse_group_name(s, chars)?;
+                            let pattern = Box::new(parse_group(s, chars, nested_level + 1, dot_all)?);
+                            current.push(Pattern::NamedCapture { name, pattern });
+                        } else {
+                            dot_all = parse_flags(s, chars, dot_all)?;
+                        }
+                    }
                     '(' => {
-                        let nested = parse_group(s, chars, nested_level + 1)?;
-                        if nested_level > 0 {
+                        let nested = parse_group(s, chars, nested_level + 1, dot_all)?;
+                        if nested.has_capture_group() {
                             current.push(Pattern::NestedCapture(Box::new(nested)));
                         } else {
                             current.push(Pattern::CaptureGroup(Box::new(nested)));
                         }
                     },
                     ')' => {
-                        if !current.is_empty() {
-                            alternatives.push(if current.len() == 1 {
-                                current.pop().unwrap()
-                            } else {
-                                Pattern::Sequence(std::mem::take(&mut current))
-                            });
+                        if nested_level == 0 {
+                            return Err(format!("Unmatched ')' in {:?}", s));
                         }
-                        return if alternatives.len() == 1 {
-                            Ok(alternatives.pop().unwrap())
-                        } else {
-                            Ok(Pattern::Alternation(alternatives))
+                        push_branch(&mut alternatives, &mut current);
+                        return match alternatives.len() {
+                            0 => Err(format!("Empty group in {:?}", s)),
+                            1 => Ok(alternatives.pop().unwrap()),
+                            _ => Ok(Pattern::OneOf(alternatives)),
                         };
                     },
                     '\\' => match chars.next() {
                         Some('w') => current.push(Pattern::AlphaNumeric),
-                        Some('d') => current.push(Pattern::CharacterSet { chars: "0123456789".to_string(), negated: false }),
-                        Some(d) if d.is_digit(10) => {
+                        Some('d') => current.push(Pattern::CharacterSet { chars: DIGITS.to_string(), negated: false }),
+                        Some('D') => current.push(Pattern::CharacterSet { chars: DIGITS.to_string(), negated: true }),
+                        Some('s') => current.push(Pattern::CharacterSet { chars: SPACES.to_string(), negated: false }),
+                        Some('S') => current.push(Pattern::CharacterSet { chars: SPACES.to_string(), negated: true }),
+                        // Half word boundaries, which only look at one side
+                        Some('b') if chars.as_str().starts_with("{start-half}") => {
+                            *chars = chars.as_str()["{start-half}".len()..].chars();
+                            current.push(Pattern::NoWordBefore);
+                        }
+                        Some('b') if chars.as_str().starts_with("{end-half}") => {
+                            *chars = chars.as_str()["{end-half}".len()..].chars();
+                            current.push(Pattern::NoWordAfter);
+                        }
+                        Some('n') => current.push(Pattern::ExactChar('\n')),
+                        Some('t') => current.push(Pattern::ExactChar('\t')),
+                        Some('r') => current.push(Pattern::ExactChar('\r')),
+                        Some(d @ '1'..='9') => {
                             let backreference = d.to_digit(10).unwrap() as usize;
                             current.push(Pattern::Backreference(backreference));
                         }
                         Some(c) => current.push(Pattern::ExactChar(c)),
                         None => return Err(format!("Unterminated escape in {:?}", s)),
                     },
+                    // With the `s` flag `.` is any character at all, even a newline
+                    '.' if dot_all => current.push(Pattern::CharacterSet { chars: String::new(), negated: true }),
                     '.' => current.push(Pattern::AnyChar),
                     '*' => {
                         match current.pop() {
@@ -63,9 +173,9 @@ impl FromStr for Pattern {
                         let mut chars_set = String::new();
                         let mut found_end = false;
                         let mut negated = false;
-                        while let Some(c2) = chars.next() {
+                        for c2 in chars.by_ref() {
                             match c2 {
-                                '^' if chars_set.is_empty() => negated = true,
+                                '^' if chars_set.is_empty() && !negated => negated = true,
                                 ']' => {
                                     found_end = true;
                                     break;
@@ -79,35 +189,20 @@ impl FromStr for Pattern {
                         current.push(Pattern::CharacterSet { chars: chars_set, negated });
                     }
                     '^' => {
-                        if current.is_empty() && alternatives.is_empty() {
+                        if current.is_empty() {
                             current.push(Pattern::StartOfLine);
                         } else {
                             current.push(Pattern::ExactChar('^'));
                         }
                     },
                     '$' => {
-                        if chars.as_str().is_empty() {
-                            if !current.is_empty() {
-                                alternatives.push(if current.len() == 1 {
-                                    current.pop().unwrap()
-                                } else {
-                                    Pattern::Sequence(std::mem::take(&mut current))
-                                });
-                            }
-                            alternatives.push(Pattern::EndOfLine);
+                        if matches!(chars.clone().next(), None | Some(')') | Some('|')) {
+                            current.push(Pattern::EndOfLine);
                         } else {
                             current.push(Pattern::ExactChar('$'));
                         }
                     },
-                    '|' => {
-                        if !current.is_empty() {
-                            alternatives.push(if current.len() == 1 {
-                                current.pop().unwrap()
-                            } else {
-                                Pattern::Sequence(std::mem::take(&mut current))
-                            });
-                        }
-                    },
+                    '|' => push_branch(&mut alternatives, &mut current),
                     '+' => {
                         match current.pop() {
                             Some(p) => current.push(Pattern::OneOrMore(Box::new(p))),
@@ -121,29 +216,7 @@ impl FromStr for Pattern {
                         }
                     }
                     '{' => {
-                        let mut min = 0;
-                        let mut max = None;
-                        let mut num_str = String::new();
-                        while let Some(c) = chars.next() {
-                            match c {
-                                '0'..='9' => num_str.push(c),
-                                ',' => {
-                                    min = num_str.parse().map_err(|_| "Invalid repeat count".to_string())?;
-                                    num_str.clear();
-                                },
-                                '}' => {
-                                    if !num_str.is_empty() {
-                                        if min == 0 {
-                                            min = num_str.parse().map_err(|_| "Invalid repeat count".to_string())?;
-                                        } else {
-                                            max = Some(num_str.parse().map_err(|_| "Invalid repeat count".to_string())?);
-                                        }
-                                    }
-                                    break;
-                                },
-                                _ => return Err("Invalid character in repeat".to_string()),
-                            }
-                        }
+                        let (min, max) = parse_repeat(chars)?;
                         if let Some(last) = current.pop() {
                             current.push(Pattern::Repeated {
                                 min,
@@ -158,24 +231,23 @@ impl FromStr for Pattern {
                 }
             }
 
-            if !current.is_empty() {
-                alternatives.push(if current.len() == 1 {
-                    current.pop().unwrap()
-                } else {
-                    Pattern::Sequence(current)
-                });
+            if nested_level > 0 {
+                return Err(format!("Unterminated '(' group in {:?}", s));
             }
 
+            push_branch(&mut alternatives, &mut current);
+
             if alternatives.len() == 1 {
                 Ok(alternatives.pop().unwrap())
             } else if alternatives.len() > 1 {
-                Ok(Pattern::Alternation(alternatives))
+                Ok(Pattern::OneOf(alternatives))
             } else {
-                Err("Empty pattern".into())
+                // The empty pattern matches everywhere
+                Ok(Pattern::Sequence(vec![]))
             }
         }
 
         // Start parsing from the root level
-        parse_group(s, &mut s.chars(), 0)
+        parse_group(s, &mut s.chars(), 0, false)
     }
-}
\ No newline at end of file
+}
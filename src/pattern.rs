@@ -1,7 +1,28 @@
-// Define the Pattern enum to represent different regex pattern elements
+// Characters of the `\d` and `\s` classes
+pub(crate) const DIGITS: &str = "0123456789";
+pub(crate) const SPACES: &str = " \t\n\r\x0b\x0c";
+
+// The characters of a set in an order that `[...]` parses back to the same set, None if there
+// is none: `]` always ends a set, and a set of only `^` reads as a negated empty one
+pub(crate) fn set_chars(chars: &str, negated: bool) -> Option<String> {
+    if chars.contains(']') {
+        return None;
+    }
+    if negated || !chars.starts_with('^') {
+        return Some(chars.to_string());
+    }
+    let (i, c) = chars.char_indices().find(|&(_, c)| c != '^')?;
+    Some(format!("{}{}{}", c, &chars[..i], &chars[i + c.len_utf8()..]))
+}
+
+// Define the Pattern enum to represent different regex pattern elements.
+// Serialized externally tagged with snake_case variant names, see the README.
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
 pub enum Pattern {
     ExactChar(char),
+    // A run of exact characters, as merged by normalization
+    Literal(String),
     AnyChar,
     AlphaNumeric,
     Sequence(Vec<Pattern>),
@@ -10,6 +31,7 @@ pub enum Pattern {
         max: Option<usize>,
         pattern: Box<Pattern>,
     },
+    // Non-capturing choice between patterns
     OneOf(Vec<Pattern>),
     CharacterSet {
         chars: String,
@@ -17,10 +39,134 @@ pub enum Pattern {
     },
     StartOfLine,
     EndOfLine,
+    // Zero-width assertions that no word character comes before or after this point
+    NoWordBefore,
+    NoWordAfter,
     OneOrMore(Box<Pattern>),
     ZeroOrOne(Box<Pattern>),
+    // Non-capturing choice like OneOf, kept for patterns built by hand. The parser
+    // makes OneOf, and normalize turns this into it.
     Alternation(Vec<Pattern>),
+    // 1-based index of a capture group, counted by opening parenthesis
     Backreference(usize),
     CaptureGroup(Box<Pattern>),
+    // A capture group that contains other capture groups
     NestedCapture(Box<Pattern>),
-}
\ No newline at end of file
+    // A capture group that can also be referred to by name
+    NamedCapture {
+        name: String,
+        pattern: Box<Pattern>,
+    },
+}
+
+impl Pattern {
+    // Match exactly this text, which is not parsed as a regex
+    pub fn literal(text: &str) -> Pattern {
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (None, _) => Pattern::Sequence(vec![]),
+            (Some(c), None) => Pattern::ExactChar(c),
+            _ => Pattern::Literal(text.to_string()),
+        }
+    }
+
+    // Match any of several patterns in a single pass. Backreferences in each
+    // pattern keep referring to that pattern's own groups.
+    pub fn any_of(mut patterns: Vec<Pattern>) -> Pattern {
+        if patterns.len() == 1 {
+            return patterns.pop().unwrap();
+        }
+        let mut group_offset = 0;
+        let mut alternatives = Vec::with_capacity(patterns.len());
+        for pattern in patterns {
+            let group_count = pattern.group_count();
+            alternatives.push(pattern.offset_backreferences(group_offset));
+            group_offset += group_count;
+        }
+        Pattern::OneOf(alternatives)
+    }
+
+    // Only match whole words, as for `grep -w`
+    pub fn whole_word(self) -> Pattern {
+        Pattern::Sequence(vec![Pattern::NoWordBefore, self, Pattern::NoWordAfter])
+    }
+
+    // Only match whole lines, as for `grep -x`
+    pub fn whole_line(self) -> Pattern {
+        Pattern::Sequence(vec![Pattern::StartOfLine, self, Pattern::EndOfLine])
+    }
+
+    // Shift every backreference by `offset` groups
+    fn offset_backreferences(self, offset: usize) -> Pattern {
+        let shift = |patterns: Vec<Pattern>| -> Vec<Pattern> {
+            patterns.into_iter().map(|p| p.offset_backreferences(offset)).collect()
+        };
+        let shift_box = |pattern: Box<Pattern>| Box::new(pattern.offset_backreferences(offset));
+        match self {
+            Pattern::Backreference(n) => Pattern::Backreference(n + offset),
+            Pattern::Sequence(patterns) => Pattern::Sequence(shift(patterns)),
+            Pattern::OneOf(patterns) => Pattern::OneOf(shift(patterns)),
+            Pattern::Alternation(patterns) => Pattern::Alternation(shift(patterns)),
+            Pattern::Repeated { min, max, pattern } => Pattern::Repeated { min, max, pattern: shift_box(pattern) },
+            Pattern::OneOrMore(pattern) => Pattern::OneOrMore(shift_box(pattern)),
+            Pattern::ZeroOrOne(pattern) => Pattern::ZeroOrOne(shift_box(pattern)),
+            Pattern::CaptureGroup(pattern) => Pattern::CaptureGroup(shift_box(pattern)),
+            Pattern::NestedCapture(pattern) => Pattern::NestedCapture(shift_box(pattern)),
+            Pattern::NamedCapture { name, pattern } => Pattern::NamedCapture { name, pattern: shift_box(pattern) },
+            pattern => pattern,
+        }
+    }
+
+    // Number of capture groups within this pattern, including itself
+    pub(crate) fn group_count(&self) -> usize {
+        match self {
+            Pattern::Sequence(patterns) | Pattern::OneOf(patterns) | Pattern::Alternation(patterns) => {
+                patterns.iter().map(Pattern::group_count).sum()
+            }
+            Pattern::Repeated { pattern, .. }
+            | Pattern::OneOrMore(pattern)
+            | Pattern::ZeroOrOne(pattern) => pattern.group_count(),
+            Pattern::CaptureGroup(pattern) | Pattern::NestedCapture(pattern) | Pattern::NamedCapture { pattern, .. } => {
+                1 + pattern.group_count()
+            }
+            _ => 0,
+        }
+    }
+
+    // Whether this pattern contains a capture group
+    pub(crate) fn has_capture_group(&self) -> bool {
+        self.group_count() > 0
+    }
+
+    // Names of the capture groups in group order, None for unnamed groups
+    pub fn capture_names(&self) -> Vec<Option<&str>> {
+        fn collect<'p>(pattern: &'p Pattern, names: &mut Vec<Option<&'p str>>) {
+            match pattern {
+                Pattern::Sequence(patterns) | Pattern::OneOf(patterns) | Pattern::Alternation(patterns) => {
+                    patterns.iter().for_each(|p| collect(p, names));
+                }
+                Pattern::Repeated { pattern, .. } | Pattern::OneOrMore(pattern) | Pattern::ZeroOrOne(pattern) => {
+                    collect(pattern, names);
+                }
+                Pattern::CaptureGroup(pattern) | Pattern::NestedCapture(pattern) => {
+                    names.push(None);
+                    collect(pattern, names);
+                }
+                Pattern::NamedCapture { name, pattern } => {
+                    names.push(Some(name));
+                    collect(pattern, names);
+                }
+                _ => {}
+            }
+        }
+
+        let mut names = Vec::with_capacity(self.group_count());
+        collect(self, &mut names);
+        names
+    }
+
+    // Number of the first capture group with the given name
+    pub fn capture_index(&self, name: &str) -> Option<usize> {
+        self.capture_names().iter().position(|&n| n == Some(name)).map(|i| i + 1)
+    }
+}
@@ -0,0 +1,138 @@
+use std::io::{self, Read};
+
+// Bytes requested from the underlying reader at a time. Reads end at multiples of this
+// in the stream, so which lines are read along with a NUL byte doesn't depend on the
+// lengths of the lines before it, unless the reader returns less than requested.
+pub(crate) const CHUNK_SIZE: usize = 64 * 1024;
+
+// Reads lines from a stream in chunks, keeping only the current chunk in memory.
+// The buffer only grows to hold a line that is longer than a chunk.
+pub struct LineReader<R> {
+    reader: R,
+    terminator: u8,
+    buffer: Vec<u8>,
+    // Unconsumed bytes are `buffer[start..end]`
+    start: usize,
+    end: usize,
+    // Byte offset in the stream of `buffer[start]`
+    offset: usize,
+    eof: bool,
+    // Whether a NUL byte has been read, which marks the input as binary
+    // unless NUL is the line terminator
+    seen_nul: bool,
+}
+
+// A line handed out by a LineReader
+pub struct RawLine<'b> {
+    // Byte offset of the line in the stream
+    pub offset: usize,
+    // The line including its terminator
+    pub bytes: &'b [u8],
+    // Whether a NUL byte has been read so far, up to the end of the current chunk
+    pub seen_nul: bool,
+}
+
+// The kinds of line endings seen in an input
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LineEndings {
+    pub lf: bool,
+    pub crlf: bool,
+}
+
+impl LineEndings {
+    // Line endings of all the newline-terminated lines in the bytes
+    pub fn detect(bytes: &[u8]) -> Self {
+        let mut endings = LineEndings::default();
+        for line in bytes.split_inclusive(|&b| b == b'\n') {
+            endings.add(line);
+        }
+        endings
+    }
+
+    // Record the ending of one line
+    pub fn add(&mut self, line: &[u8]) {
+        if line.ends_with(b"\r\n") {
+            self.crlf = true;
+        } else if line.ends_with(b"\n") {
+            self.lf = true;
+        }
+    }
+
+    // Whether some lines end with CRLF and others with a bare LF
+    pub fn is_mixed(&self) -> bool {
+        self.lf && self.crlf
+    }
+}
+
+impl<R: Read> LineReader<R> {
+    // Read lines ended by `terminator`
+    pub fn new(reader: R, terminator: u8) -> Self {
+        LineReader {
+            reader,
+            terminator,
+            buffer: vec![0; CHUNK_SIZE],
+            start: 0,
+            end: 0,
+            offset: 0,
+            eof: false,
+            seen_nul: false,
+        }
+    }
+
+    // The next line in the stream. Returns as soon as a whole line is available
+    // rather than waiting to fill the buffer.
+    pub fn next_line(&mut self) -> io::Result<Option<RawLine<'_>>> {
+        let mut searched = self.start;
+        let line_end = loop {
+            if let Some(i) = self.buffer[searched..self.end].iter().position(|&b| b == self.terminator) {
+                break searched + i + 1;
+            }
+            if self.eof {
+                if self.start == self.end {
+                    return Ok(None);
+                }
+                break self.end;
+            }
+            let filled_to = self.end;
+            searched = filled_to - self.fill()?;
+        };
+
+        let line_start = self.start;
+        let offset = self.offset;
+        self.start = line_end;
+        self.offset += line_end - line_start;
+        Ok(Some(RawLine {
+            offset,
+            bytes: &self.buffer[line_start..line_end],
+            seen_nul: self.seen_nul,
+        }))
+    }
+
+    // Read more of the stream after the unconsumed bytes, first moving them to the
+    // front of the buffer. Returns how far they moved.
+    fn fill(&mut self) -> io::Result<usize> {
+        let shift = self.start;
+        if shift > 0 {
+            self.buffer.copy_within(self.start..self.end, 0);
+            self.start = 0;
+            self.end -= shift;
+        }
+        let stream_end = self.offset + (self.end - self.start);
+        let read_end = self.end + (CHUNK_SIZE - stream_end % CHUNK_SIZE);
+        if read_end > self.buffer.len() {
+            self.buffer.resize(read_end.max(self.buffer.len() * 2), 0);
+        }
+        let read = loop {
+            match self.reader.read(&mut self.buffer[self.end..read_end]) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result?,
+            }
+        };
+        if read == 0 {
+            self.eof = true;
+        }
+        self.seen_nul |= self.terminator != 0 && self.buffer[self.end..self.end + read].contains(&0);
+        self.end += read;
+        Ok(shift)
+    }
+}
@@ -0,0 +1,92 @@
+use crate::Pattern;
+use std::fmt;
+
+// A repetition over a body that consecutive iterations can split the same text between in
+// many ways, so that a match failing after it retries every one of them
+#[derive(Clone, Debug, PartialEq)]
+pub struct BacktrackRisk {
+    // The outer repetition, and the repetition in its body that makes the split ambiguous
+    pub repetition: Pattern,
+    pub inner: Pattern,
+}
+
+impl fmt::Display for BacktrackRisk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} repeats {} and can backtrack catastrophically",
+            self.repetition, self.inner
+        )
+    }
+}
+
+impl Pattern {
+    // Nested repetitions that can take exponential time to fail, outermost first
+    pub fn backtrack_risks(&self) -> Vec<BacktrackRisk> {
+        let mut risks = vec![];
+        collect_risks(self, &mut risks);
+        risks
+    }
+}
+
+fn collect_risks(pattern: &Pattern, risks: &mut Vec<BacktrackRisk>) {
+    match pattern {
+        Pattern::Repeated { max, pattern: body, .. } => {
+            if repeats(*max) {
+                push_risk(pattern, body, risks);
+            }
+            collect_risks(body, risks);
+        }
+        Pattern::OneOrMore(body) => {
+            push_risk(pattern, body, risks);
+            collect_risks(body, risks);
+        }
+        Pattern::ZeroOrOne(body) => collect_risks(body, risks),
+        Pattern::Sequence(patterns) | Pattern::OneOf(patterns) | Pattern::Alternation(patterns) => {
+            patterns.iter().for_each(|p| collect_risks(p, risks));
+        }
+        Pattern::CaptureGroup(pattern) | Pattern::NestedCapture(pattern) | Pattern::NamedCapture { pattern, .. } => {
+            collect_risks(pattern, risks);
+        }
+        _ => {}
+    }
+}
+
+fn push_risk(repetition: &Pattern, body: &Pattern, risks: &mut Vec<BacktrackRisk>) {
+    if let Some(inner) = ambiguous_repetition(body) {
+        risks.push(BacktrackRisk {
+            repetition: repetition.clone(),
+            inner: inner.clone(),
+        });
+    }
+}
+
+// Whether a repetition allows more than one iteration
+fn repeats(max: Option<usize>) -> bool {
+    max.map_or(true, |max| max > 1)
+}
+
+// A repetition of non-empty text that the body can consist of on its own, as everything
+// else in the body may match nothing
+fn ambiguous_repetition(body: &Pattern) -> Option<&Pattern> {
+    match body {
+        Pattern::Repeated { max, pattern, .. } if repeats(*max) && pattern.info().min_len > 0 => Some(body),
+        Pattern::OneOrMore(pattern) if pattern.info().min_len > 0 => Some(body),
+        Pattern::Repeated { pattern, .. }
+        | Pattern::OneOrMore(pattern)
+        | Pattern::ZeroOrOne(pattern)
+        | Pattern::CaptureGroup(pattern)
+        | Pattern::NestedCapture(pattern)
+        | Pattern::NamedCapture { pattern, .. } => ambiguous_repetition(pattern),
+        Pattern::OneOf(branches) | Pattern::Alternation(branches) => branches.iter().find_map(ambiguous_repetition),
+        Pattern::Sequence(parts) => {
+            let required: Vec<_> = parts.iter().filter(|part| part.info().min_len > 0).collect();
+            match required.as_slice() {
+                [] => parts.iter().find_map(ambiguous_repetition),
+                [part] => ambiguous_repetition(part),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
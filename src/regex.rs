@@ -0,0 +1,200 @@
+use crate::matcher::{Captures, MatchConfig, MatchError, Matcher};
+use crate::parser::parse_pattern;
+use crate::Pattern;
+use std::ops::Range;
+
+// A pattern together with how it is matched
+#[derive(Clone, Debug, PartialEq)]
+pub struct Regex {
+    pattern: Pattern,
+    config: MatchConfig,
+    backtrack_limit: Option<usize>,
+}
+
+// Iterator over the pieces of a text between matches, ending with an error if
+// matching gives up
+pub struct Split<'r, 't> {
+    regex: &'r Regex,
+    text: &'t str,
+    // Where the next search starts, and where the next piece starts
+    pos: usize,
+    piece_start: usize,
+    last_end: Option<usize>,
+    done: bool,
+}
+
+// Iterator over at most a given number of pieces, the last holding the rest of the text
+pub struct SplitN<'r, 't> {
+    split: Split<'r, 't>,
+    remaining: usize,
+}
+
+// Options for building a `Regex`
+#[derive(Clone, Debug)]
+pub struct RegexBuilder {
+    pattern: String,
+    config: MatchConfig,
+    backtrack_limit: Option<usize>,
+}
+
+impl RegexBuilder {
+    pub fn new(pattern: &str) -> Self {
+        RegexBuilder {
+            pattern: pattern.to_string(),
+            config: MatchConfig::default(),
+            backtrack_limit: None,
+        }
+    }
+
+    // How the text is split into lines
+    pub fn config(mut self, config: MatchConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    // Give up with `MatchError::BudgetExceeded` once a search has tried this many
+    // pattern nodes, rather than backtracking for as long as the pattern needs
+    pub fn backtrack_limit(mut self, limit: usize) -> Self {
+        self.backtrack_limit = Some(limit);
+        self
+    }
+
+    pub fn build(&self) -> Result<Regex, String> {
+        Ok(Regex {
+            pattern: parse_pattern(&self.pattern)?,
+            config: self.config,
+            backtrack_limit: self.backtrack_limit,
+        })
+    }
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex, String> {
+        RegexBuilder::new(pattern).build()
+    }
+
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    // Whether the pattern matches anywhere in the text
+    pub fn is_match(&self, text: &str) -> Result<bool, MatchError> {
+        Ok(self.find(text)?.is_some())
+    }
+
+    // Byte span of the leftmost match in the text
+    pub fn find(&self, text: &str) -> Result<Option<Range<usize>>, MatchError> {
+        self.find_at(text, 0)
+    }
+
+    // Byte span of the leftmost match starting at or after `start`
+    pub fn find_at(&self, text: &str, start: usize) -> Result<Option<Range<usize>>, MatchError> {
+        Ok(self.captures_at(text, start)?.and_then(|captures| captures.get(0)))
+    }
+
+    // Captures of the leftmost match in the text
+    pub fn captures(&self, text: &str) -> Result<Option<Captures>, MatchError> {
+        self.captures_at(text, 0)
+    }
+
+    // Captures of the leftmost match starting at or after `start`
+    pub fn captures_at(&self, text: &str, start: usize) -> Result<Option<Captures>, MatchError> {
+        Matcher::captures_limited(&self.pattern, text, start, self.config, self.backtrack_limit)
+    }
+
+    // The pieces of the text between non-overlapping matches. An empty match splits the
+    // text where it is found, unless it directly follows the previous match.
+    pub fn split<'r, 't>(&'r self, text: &'t str) -> Split<'r, 't> {
+        Split {
+            regex: self,
+            text,
+            pos: 0,
+            piece_start: 0,
+            last_end: None,
+            done: false,
+        }
+    }
+
+    // Like `split`, but with at most `limit` pieces, the last holding the rest of the text
+    pub fn splitn<'r, 't>(&'r self, text: &'t str, limit: usize) -> SplitN<'r, 't> {
+        SplitN {
+            split: self.split(text),
+            remaining: limit,
+        }
+    }
+}
+
+// A pattern matched with the default configuration and no backtracking limit
+impl From<Pattern> for Regex {
+    fn from(pattern: Pattern) -> Self {
+        Regex {
+            pattern,
+            config: MatchConfig::default(),
+            backtrack_limit: None,
+        }
+    }
+}
+
+impl<'t> Iterator for Split<'_, 't> {
+    type Item = Result<&'t str, MatchError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        loop {
+            let found = if self.pos <= self.text.len() {
+                self.regex.find_at(self.text, self.pos)
+            } else {
+                Ok(None)
+            };
+            match found {
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+                Ok(None) => return self.rest().map(Ok),
+                Ok(Some(found)) if found.is_empty() && self.last_end == Some(found.end) => {
+                    // Skip an empty match right after the previous match
+                    self.pos = found.start + self.text[found.start..].chars().next().map_or(1, char::len_utf8);
+                }
+                Ok(Some(found)) => {
+                    let piece = &self.text[self.piece_start..found.start];
+                    self.pos = found.end;
+                    self.piece_start = found.end;
+                    self.last_end = Some(found.end);
+                    return Some(Ok(piece));
+                }
+            }
+        }
+    }
+}
+
+impl<'t> Split<'_, 't> {
+    // The text after the last match, which ends the iteration
+    fn rest(&mut self) -> Option<&'t str> {
+        if self.done {
+            return None;
+        }
+        self.done = true;
+        Some(&self.text[self.piece_start..])
+    }
+}
+
+impl<'t> Iterator for SplitN<'_, 't> {
+    type Item = Result<&'t str, MatchError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.remaining {
+            0 => None,
+            1 => {
+                self.remaining = 0;
+                self.split.rest().map(Ok)
+            }
+            _ => {
+                self.remaining -= 1;
+                self.split.next()
+            }
+        }
+    }
+}
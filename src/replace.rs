@@ -0,0 +1,85 @@
+use crate::{Captures, Pattern};
+
+// A --replace template, with `$1`, `${1}`, `$name` or `${name}` standing for a
+// capture group and `$$` for a literal dollar sign, as in ripgrep
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    pieces: Vec<Piece>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Piece {
+    Literal(String),
+    // Group number, with the whole match as group 0
+    Group(usize),
+    // A group the pattern doesn't have, which expands to nothing
+    Missing,
+}
+
+impl Template {
+    // Parse a template, resolving group names against the pattern it is used with
+    pub fn new(template: &str, pattern: &Pattern) -> Template {
+        let mut pieces = vec![];
+        let mut literal = String::new();
+        let mut rest = template;
+        while let Some(i) = rest.find('$') {
+            literal.push_str(&rest[..i]);
+            rest = &rest[i + 1..];
+            if let Some(after) = rest.strip_prefix('$') {
+                literal.push('$');
+                rest = after;
+                continue;
+            }
+
+            // A name is either braced or the longest run of word characters
+            let (name, after) = match rest.strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) => (&braced[..end], &braced[end + 1..]),
+                    None => ("", rest),
+                },
+                None => {
+                    let end = rest
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+            if name.is_empty() {
+                // Not a reference after all
+                literal.push('$');
+                continue;
+            }
+            if !literal.is_empty() {
+                pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+            }
+            let group = match name.parse::<usize>() {
+                Ok(number) => Some(number).filter(|&n| n <= pattern.group_count()),
+                Err(_) => pattern.capture_index(name),
+            };
+            pieces.push(group.map_or(Piece::Missing, Piece::Group));
+            rest = after;
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+        Template { pieces }
+    }
+
+    // The replacement for one match of the pattern in `text`
+    pub fn expand(&self, text: &str, captures: &Captures) -> String {
+        let mut expanded = String::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Literal(literal) => expanded.push_str(literal),
+                Piece::Group(group) => {
+                    if let Some(span) = captures.get(*group) {
+                        expanded.push_str(&text[span]);
+                    }
+                }
+                Piece::Missing => {}
+            }
+        }
+        expanded
+    }
+}
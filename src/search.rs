@@ -0,0 +1,794 @@
+use crate::color::Colors;
+use crate::json::{self, Value};
+use crate::options::{BinaryFiles, Options, OutputMode};
+use crate::reader::{LineEndings, LineReader, CHUNK_SIZE};
+use crate::replace::Template;
+use crate::{MatchConfig, Matcher, Pattern};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::io::{self, Read, Write};
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+// Searches inputs line by line and writes results in the selected output mode
+pub struct Searcher<'a> {
+    pattern: &'a Pattern,
+    options: &'a Options,
+    with_filename: bool,
+    colors: Option<Colors>,
+    // Ends each line of input and output, NUL for -z
+    line_terminator: u8,
+    // Totals over all inputs searched so far, for the --json summary
+    totals: Cell<Stats>,
+    // Printed in place of each match in selected lines
+    replacement: Option<Template>,
+}
+
+// A line of input without its terminator, owned once it outlives the read buffer
+struct Line<'t> {
+    number: usize,
+    offset: usize,
+    text: Cow<'t, str>,
+    // The line as read, including its terminator
+    raw: Cow<'t, [u8]>,
+}
+
+// The part of a -U match on one line. With --replace, the line where the match starts
+// holds the replacement of the whole match and the rest of it is replaced by nothing.
+struct MatchPart {
+    span: Range<usize>,
+    replaced: Option<String>,
+}
+
+// Maps offsets in text decoded with replacement characters back to offsets in its bytes
+#[derive(Default)]
+struct RawOffsets {
+    // Where each replacement character ends in the text, and in the bytes
+    replacements: Vec<(usize, usize)>,
+}
+
+impl RawOffsets {
+    fn new(bytes: &[u8]) -> Self {
+        let mut replacements = vec![];
+        let (mut text_end, mut raw_end) = (0, 0);
+        for chunk in bytes.utf8_chunks() {
+            text_end += chunk.valid().len();
+            raw_end += chunk.valid().len();
+            if !chunk.invalid().is_empty() {
+                text_end += char::REPLACEMENT_CHARACTER.len_utf8();
+                raw_end += chunk.invalid().len();
+                replacements.push((text_end, raw_end));
+            }
+        }
+        RawOffsets { replacements }
+    }
+
+    // The offset in the bytes of a character boundary in the text
+    fn get(&self, offset: usize) -> usize {
+        let before = self.replacements.partition_point(|&(text_end, _)| text_end <= offset);
+        match before.checked_sub(1).map(|i| self.replacements[i]) {
+            Some((text_end, raw_end)) => raw_end + (offset - text_end),
+            None => offset,
+        }
+    }
+}
+
+impl Line<'_> {
+    // Offsets into the line as read, which only differ from those in its text after invalid UTF-8
+    fn raw_offsets(&self) -> RawOffsets {
+        if self.raw.starts_with(self.text.as_bytes()) {
+            RawOffsets::default()
+        } else {
+            RawOffsets::new(&self.raw)
+        }
+    }
+}
+
+// What --json reports about the search of one input, or of all of them
+#[derive(Clone, Copy, Debug, Default)]
+struct Stats {
+    elapsed: Duration,
+    searches: u64,
+    searches_with_match: u64,
+    bytes_searched: u64,
+    bytes_printed: u64,
+    matched_lines: u64,
+    matches: u64,
+}
+
+// Progress through the lines of one input
+struct SearchState {
+    // Ring buffer of the lines preceding the next match
+    before: VecDeque<Line<'static>>,
+    after_remaining: usize,
+    last_printed: Option<usize>,
+    count: usize,
+    // Set once the input turns out to be binary, to where that was found
+    binary_offset: Option<usize>,
+    // Line endings seen so far, tracked with --crlf
+    line_endings: LineEndings,
+    started: Instant,
+    // Whether the --json begin message has been written
+    begun: bool,
+    stats: Stats,
+}
+
+impl Stats {
+    fn add(&mut self, other: &Stats) {
+        self.elapsed += other.elapsed;
+        self.searches += other.searches;
+        self.searches_with_match += other.searches_with_match;
+        self.bytes_searched += other.bytes_searched;
+        self.bytes_printed += other.bytes_printed;
+        self.matched_lines += other.matched_lines;
+        self.matches += other.matches;
+    }
+
+    fn to_json(self) -> Value<'static> {
+        Value::Object(vec![
+            ("elapsed", json::duration(self.elapsed)),
+            ("searches", Value::Number(self.searches)),
+            ("searches_with_match", Value::Number(self.searches_with_match)),
+            ("bytes_searched", Value::Number(self.bytes_searched)),
+            ("bytes_printed", Value::Number(self.bytes_printed)),
+            ("matched_lines", Value::Number(self.matched_lines)),
+            ("matches", Value::Number(self.matches)),
+        ])
+    }
+}
+
+// Separators between the prefix fields and the text of a line
+const MATCH_SEPARATOR: char = ':';
+const CONTEXT_SEPARATOR: char = '-';
+
+impl<'a> Searcher<'a> {
+    pub fn new(pattern: &'a Pattern, options: &'a Options, with_filename: bool) -> Self {
+        Searcher {
+            pattern,
+            options,
+            with_filename,
+            colors: None,
+            line_terminator: if options.null_data { b'\0' } else { b'\n' },
+            totals: Cell::new(Stats::default()),
+            replacement: options.replace.as_deref().map(|template| Template::new(template, pattern)),
+        }
+    }
+
+    // Highlight matches and prefixes with the given colors
+    pub fn with_colors(mut self, colors: Colors) -> Self {
+        self.colors = Some(colors);
+        self
+    }
+
+    // Search one input as it is read and write its results, returning the number of selected lines
+    pub fn search(&self, name: &str, mut input: impl Read, out: &mut dyn Write) -> io::Result<usize> {
+        // Matches that span lines need the whole input at once
+        if self.options.multiline {
+            let mut buffer = Vec::new();
+            input.read_to_end(&mut buffer)?;
+            return self.search_buffer(name, &buffer, out);
+        }
+        let mut state = self.start_state();
+        let mut reader = LineReader::new(input, self.line_terminator);
+        let mut number = 0;
+        while let Some(raw_line) = reader.next_line()? {
+            // Invalid UTF-8 is matched with replacement characters and marks the input as binary
+            let offset = raw_line.offset;
+            if self.options.crlf {
+                state.line_endings.add(raw_line.bytes);
+            }
+            state.stats.bytes_searched += raw_line.bytes.len() as u64;
+            let text = String::from_utf8_lossy(raw_line.bytes);
+            let invalid = match text {
+                Cow::Owned(_) => std::str::from_utf8(raw_line.bytes).err().map(|e| offset + e.valid_up_to()),
+                Cow::Borrowed(_) => None,
+            };
+            if let Some(binary_offset) = raw_line.seen_nul.then_some(offset).or(invalid) {
+                state.binary_offset.get_or_insert(binary_offset);
+            }
+            if state.binary_offset.is_some() && self.options.binary_files == BinaryFiles::WithoutMatch {
+                break;
+            }
+            number += 1;
+            let line = self.line(number, offset, text, raw_line.bytes);
+            if !self.handle_line(out, name, &mut state, line, |text| Matcher::find(self.pattern, text), None)? {
+                break;
+            }
+        }
+        self.finish(out, name, &mut state)?;
+        Ok(state.count)
+    }
+
+    // Search a whole input held in memory, such as a mapped file, and write its results.
+    // The pattern runs across the entire buffer and line boundaries are only recovered
+    // around its matches, so lines that can't be selected or printed are never visited.
+    pub fn search_buffer(&self, name: &str, buffer: &[u8], out: &mut dyn Write) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buffer);
+        let terminator = char::from(self.line_terminator);
+        let config = MatchConfig {
+            line_terminator: Some(terminator),
+            crlf: self.options.crlf,
+            multiline: self.options.multiline,
+        };
+        let (before_context, after_context) = self.context_sizes();
+        let every_line = self.options.invert_match || before_context > 0 || after_context > 0;
+
+        let mut state = self.start_state();
+        let invalid = std::str::from_utf8(buffer).err().map(|e| e.valid_up_to());
+        let binary = self.binary_line(buffer, invalid);
+        // Where the search stopped, which is the end of the input unless it stops early
+        let mut searched_to = buffer.len();
+        // Lines are printed from the input, at its offsets rather than those of the decoded text
+        let raw_offsets = if invalid.is_some() { RawOffsets::new(buffer) } else { RawOffsets::default() };
+        let raw_line = |start: usize, end: usize| &buffer[raw_offsets.get(start)..raw_offsets.get(end)];
+        if self.options.crlf {
+            state.line_endings = LineEndings::detect(buffer);
+        }
+        let text = text.as_ref();
+        let mut number = 0;
+        let mut pos = 0;
+        let max_count = self.options.max_count.unwrap_or(usize::MAX);
+        'lines: while pos < text.len() {
+            // Past the last match and its context, streaming stops after reading one more line
+            if state.count >= max_count && state.after_remaining == 0 {
+                searched_to = raw_offsets.get(text[pos..].find(terminator).map_or(text.len(), |i| pos + i + 1));
+                break;
+            }
+            // The start of the line holding the next match, ignoring an empty match after the last line
+            let found = Matcher::find_with(self.pattern, text, pos, config)
+                .map(|found| (text[..found.start].rfind(terminator).map_or(0, |i| i + 1), found))
+                .filter(|&(line_start, _)| line_start < text.len());
+            let skipped_end = found.as_ref().map_or(text.len(), |(line_start, _)| *line_start);
+
+            // The lines in between don't match
+            if every_line {
+                let mut offset = pos;
+                for text_line in text[pos..skipped_end].split_inclusive(terminator) {
+                    number += 1;
+                    let raw = raw_line(offset, offset + text_line.len());
+                    let line = self.line(number, raw_offsets.get(offset), Cow::Borrowed(text_line), raw);
+                    offset += text_line.len();
+                    let line_end = line.offset + raw.len();
+                    if !self.reach_binary(&mut state, &binary, &line) || !self.handle_line(out, name, &mut state, line, |_| None, None)? {
+                        searched_to = line_end;
+                        break 'lines;
+                    }
+                }
+            } else {
+                number += text[pos..skipped_end].bytes().filter(|&b| b == self.line_terminator).count();
+            }
+
+            let (line_start, found) = match found {
+                Some(found) => found,
+                None => break,
+            };
+            // A multiline match ending with a line terminator doesn't touch the next line
+            let line_end = if found.end > found.start && text[..found.end].ends_with(terminator) {
+                found.end
+            } else {
+                text[found.end..].find(terminator).map_or(text.len(), |i| found.end + i + 1)
+            };
+            // A multiline match is replaced as a whole
+            let replaced = self
+                .replacement
+                .as_ref()
+                .filter(|_| self.options.multiline)
+                .and_then(|template| Some(template.expand(text, &Matcher::captures_with(self.pattern, text, found.start, config)?)));
+            let mut offset = line_start;
+            for text_line in text[line_start..line_end].split_inclusive(terminator) {
+                number += 1;
+                let raw = raw_line(offset, offset + text_line.len());
+                let line = self.line(number, raw_offsets.get(offset), Cow::Borrowed(text_line), raw);
+                // The part of the match on this line
+                let first = found.start.clamp(offset, offset + line.text.len()) - offset
+                    ..found.end.clamp(offset, offset + line.text.len()) - offset;
+                let replaced = replaced.clone().filter(|_| offset <= found.start);
+                offset += text_line.len();
+                let raw_end = line.offset + raw.len();
+                if !self.reach_binary(&mut state, &binary, &line) || !self.handle_line(out, name, &mut state, line, |_| Some(first), replaced)? {
+                    searched_to = raw_end;
+                    break 'lines;
+                }
+            }
+            pos = line_end;
+        }
+        // Lines that weren't visited are still searched, up to the binary line with -I
+        if let Some((line, offset)) = binary.filter(|(line, _)| line.start < searched_to) {
+            state.binary_offset.get_or_insert(offset);
+            if self.options.binary_files == BinaryFiles::WithoutMatch {
+                searched_to = searched_to.min(line.end);
+            }
+        }
+        state.stats.bytes_searched = searched_to as u64;
+        self.finish(out, name, &mut state)?;
+        Ok(state.count)
+    }
+
+    // The line where the streaming search finds a whole input to be binary, with the offset
+    // it reports: the line holding the start of the chunk read with the first NUL, or the
+    // line with the first invalid UTF-8 at its position, whichever comes first
+    fn binary_line(&self, buffer: &[u8], invalid: Option<usize>) -> Option<(Range<usize>, usize)> {
+        let line_around = |pos: usize| {
+            let start = buffer[..pos].iter().rposition(|&b| b == self.line_terminator).map_or(0, |i| i + 1);
+            let end = buffer[pos..].iter().position(|&b| b == self.line_terminator).map_or(buffer.len(), |i| pos + i + 1);
+            start..end
+        };
+        let nul = buffer.iter().position(|&b| b == 0).filter(|_| self.line_terminator != 0).map(|nul| {
+            let line = line_around(nul / CHUNK_SIZE * CHUNK_SIZE);
+            let offset = line.start;
+            (line, offset)
+        });
+        let invalid = invalid.map(|invalid| (line_around(invalid), invalid));
+        match (nul, invalid) {
+            (Some(nul), Some(invalid)) if invalid.0.start < nul.0.start => Some(invalid),
+            (nul, invalid) => nul.or(invalid),
+        }
+    }
+
+    // Mark the input binary once a visited line reaches its binary line. Returns whether
+    // the line can still be searched, which it can't with -I.
+    fn reach_binary(&self, state: &mut SearchState, binary: &Option<(Range<usize>, usize)>, line: &Line) -> bool {
+        match binary {
+            Some((binary_line, offset)) if line.offset >= binary_line.start => {
+                state.binary_offset.get_or_insert(*offset);
+                self.options.binary_files != BinaryFiles::WithoutMatch
+            }
+            _ => true,
+        }
+    }
+
+    // A line without its terminator, or without its CRLF ending with --crlf
+    fn line<'t>(&self, number: usize, offset: usize, raw_line: Cow<'t, str>, raw: &'t [u8]) -> Line<'t> {
+        let terminator = char::from(self.line_terminator);
+        let text = match raw_line {
+            Cow::Borrowed(raw_line) => {
+                let raw_line = raw_line.strip_suffix(terminator).unwrap_or(raw_line);
+                Cow::Borrowed(match raw_line.strip_suffix('\r') {
+                    Some(text) if self.options.crlf => text,
+                    _ => raw_line,
+                })
+            }
+            Cow::Owned(mut raw_line) => {
+                if raw_line.ends_with(terminator) {
+                    raw_line.pop();
+                }
+                if self.options.crlf && raw_line.ends_with('\r') {
+                    raw_line.pop();
+                }
+                Cow::Owned(raw_line)
+            }
+        };
+        Line {
+            number,
+            offset,
+            text,
+            raw: Cow::Borrowed(raw),
+        }
+    }
+
+    // The matches to print on a selected line. A multiline match only shows its part on the line.
+    fn line_matches(&self, text: &str, first: &Option<Range<usize>>) -> Vec<Range<usize>> {
+        if self.options.multiline {
+            first.iter().cloned().collect()
+        } else {
+            Matcher::find_iter(self.pattern, text).collect()
+        }
+    }
+
+    // Context sizes in effect for the selected output
+    fn context_sizes(&self) -> (usize, usize) {
+        let with_context = self.options.output == OutputMode::Lines
+            && !self.options.only_matching
+            && !self.options.vimgrep;
+        if with_context {
+            (self.options.before_context, self.options.after_context)
+        } else {
+            (0, 0)
+        }
+    }
+
+    fn start_state(&self) -> SearchState {
+        SearchState {
+            before: VecDeque::with_capacity(self.context_sizes().0),
+            after_remaining: 0,
+            last_printed: None,
+            count: 0,
+            binary_offset: None,
+            line_endings: LineEndings::default(),
+            started: Instant::now(),
+            begun: false,
+            stats: Stats {
+                searches: 1,
+                ..Stats::default()
+            },
+        }
+    }
+
+    // Select, print or remember one line given a way to find its first match, and with -U
+    // and --replace, the replacement of a match starting on it. Returns whether later
+    // lines are still needed.
+    fn handle_line(
+        &self,
+        out: &mut dyn Write,
+        name: &str,
+        state: &mut SearchState,
+        line: Line,
+        find: impl FnOnce(&str) -> Option<Range<usize>>,
+        replaced: Option<String>,
+    ) -> io::Result<bool> {
+        let max_count = self.options.max_count.unwrap_or(usize::MAX);
+        let (before_context, after_context) = self.context_sizes();
+
+        // Past the last match only trailing context is printed
+        if state.count >= max_count {
+            if state.after_remaining == 0 {
+                return Ok(false);
+            }
+            state.after_remaining -= 1;
+            self.write_context(out, name, state, &line)?;
+            return Ok(true);
+        }
+
+        // Lines of a binary input are never printed, but the first selected one is reported
+        let binary_message = state.binary_offset.is_some() && self.options.binary_files == BinaryFiles::Binary;
+
+        // Lines that aren't selected can still be context
+        let first = find(&line.text);
+        if first.is_some() == self.options.invert_match {
+            if binary_message {
+                return Ok(true);
+            } else if state.after_remaining > 0 {
+                state.after_remaining -= 1;
+                self.write_context(out, name, state, &line)?;
+            } else if before_context > 0 {
+                if state.before.len() == before_context {
+                    state.before.pop_front();
+                }
+                state.before.push_back(Line {
+                    text: Cow::Owned(line.text.into_owned()),
+                    raw: Cow::Owned(line.raw.into_owned()),
+                    ..line
+                });
+            }
+            return Ok(true);
+        }
+        state.count += 1;
+        let part = first.clone().filter(|_| self.options.multiline).map(|span| MatchPart { span, replaced });
+
+        match self.options.output {
+            OutputMode::Lines if binary_message => {
+                // A JSON end message carries the binary offset instead
+                if self.options.json {
+                    self.begin(out, name, state)?;
+                } else {
+                    writeln!(out, "Binary file {} matches", name)?;
+                }
+                return Ok(false);
+            }
+            OutputMode::Lines if self.options.json => {
+                for context in std::mem::take(&mut state.before) {
+                    self.write_context(out, name, state, &context)?;
+                }
+                let matches = self.line_matches(&line.text, &first);
+                state.stats.matches += matches.len() as u64;
+                self.write_json_line(out, name, state, "match", &line, &matches, part.as_ref())?;
+                state.after_remaining = after_context;
+            }
+            OutputMode::Lines if self.options.vimgrep => {
+                let raw_offsets = line.raw_offsets();
+                for found in self.line_matches(&line.text, &first) {
+                    let start = raw_offsets.get(found.start);
+                    self.write_prefix(out, name, line.number, Some(start), line.offset + start, MATCH_SEPARATOR)?;
+                    self.write_text(out, &line, true, part.as_ref())?;
+                    self.end_line(out)?;
+                }
+            }
+            OutputMode::Lines if self.options.only_matching => {
+                let raw_offsets = line.raw_offsets();
+                for found in self.line_matches(&line.text, &first).into_iter().filter(|m| !m.is_empty()) {
+                    let raw = raw_offsets.get(found.start)..raw_offsets.get(found.end);
+                    // The rest of a replaced multiline match prints nothing
+                    if self.replacement.is_some() && part.as_ref().is_some_and(|part| part.replaced.is_none()) {
+                        continue;
+                    }
+                    self.write_prefix(out, name, line.number, Some(raw.start), line.offset + raw.start, MATCH_SEPARATOR)?;
+                    match self.replacement {
+                        Some(_) => {
+                            let replaced = self.replaced(&line.text, found, part.as_ref());
+                            self.paint(out, |colors| &colors.selected_match, replaced)?
+                        }
+                        None => self.paint_bytes(out, |colors| &colors.selected_match, &line.raw[raw])?,
+                    }
+                    self.end_line(out)?;
+                }
+            }
+            OutputMode::Lines => {
+                for context in std::mem::take(&mut state.before) {
+                    self.write_context(out, name, state, &context)?;
+                }
+                let match_start = first.map(|first| line.raw_offsets().get(first.start));
+                self.write_line(out, name, &line, MATCH_SEPARATOR, match_start, part.as_ref(), &mut state.last_printed)?;
+                state.after_remaining = after_context;
+            }
+            OutputMode::Count => {}
+            // The first match decides the result for the whole input
+            OutputMode::FilesWithMatches | OutputMode::FilesWithoutMatch | OutputMode::Quiet => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    // Write what is printed once an input has been searched
+    fn finish(&self, out: &mut dyn Write, name: &str, state: &mut SearchState) -> io::Result<()> {
+        // Lines with a bare LF keep any carriage return that was meant to end them
+        if state.line_endings.is_mixed() {
+            eprintln!("grep: {}: warning: mixed CRLF and LF line endings", name);
+        }
+        if self.options.json {
+            state.stats.elapsed = state.started.elapsed();
+            state.stats.matched_lines = state.count as u64;
+            state.stats.searches_with_match = u64::from(state.count > 0);
+            // The totals count what each input's own stats do, which leaves out its end message
+            let stats = state.stats;
+            if state.begun {
+                let binary_offset = state.binary_offset.map_or(Value::Null, |offset| Value::Number(offset as u64));
+                let data = Value::Object(vec![
+                    ("path", json::data(name.as_bytes())),
+                    ("binary_offset", binary_offset),
+                    ("stats", state.stats.to_json()),
+                ]);
+                self.write_message(out, state, "end", data)?;
+            }
+            let mut totals = self.totals.get();
+            totals.add(&stats);
+            self.totals.set(totals);
+        }
+        match self.options.output {
+            OutputMode::Count => {
+                self.write_filename(out, name, MATCH_SEPARATOR)?;
+                writeln!(out, "{}", state.count)?;
+            }
+            OutputMode::FilesWithMatches if state.count > 0 => self.write_listed_filename(out, name)?,
+            OutputMode::FilesWithoutMatch if state.count == 0 => self.write_listed_filename(out, name)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Write the --json summary of all inputs searched, which took `elapsed` in total
+    pub fn write_summary(&self, out: &mut dyn Write, elapsed: Duration) -> io::Result<()> {
+        let message = Value::Object(vec![
+            ("type", Value::String(Cow::Borrowed("summary"))),
+            (
+                "data",
+                Value::Object(vec![("elapsed_total", json::duration(elapsed)), ("stats", self.totals.get().to_json())]),
+            ),
+        ]);
+        message.write(out)?;
+        writeln!(out)
+    }
+
+    // Write one --json message, counting its bytes in the stats of the input
+    fn write_message(&self, out: &mut dyn Write, state: &mut SearchState, kind: &'static str, data: Value) -> io::Result<()> {
+        let mut message = Vec::new();
+        Value::Object(vec![("type", Value::String(Cow::Borrowed(kind))), ("data", data)]).write(&mut message)?;
+        message.push(b'\n');
+        state.stats.bytes_printed += message.len() as u64;
+        out.write_all(&message)
+    }
+
+    // Write the --json begin message for the input, unless it has been written
+    fn begin(&self, out: &mut dyn Write, name: &str, state: &mut SearchState) -> io::Result<()> {
+        if !state.begun {
+            state.begun = true;
+            let data = Value::Object(vec![("path", json::data(name.as_bytes()))]);
+            self.write_message(out, state, "begin", data)?;
+        }
+        Ok(())
+    }
+
+    // Write a selected or context line as a --json message
+    #[allow(clippy::too_many_arguments)]
+    fn write_json_line(
+        &self,
+        out: &mut dyn Write,
+        name: &str,
+        state: &mut SearchState,
+        kind: &'static str,
+        line: &Line,
+        matches: &[Range<usize>],
+        part: Option<&MatchPart>,
+    ) -> io::Result<()> {
+        self.begin(out, name, state)?;
+        // Submatches are reported at byte offsets into `lines`
+        let raw_offsets = line.raw_offsets();
+        let submatches = matches
+            .iter()
+            .map(|found| {
+                let (start, end) = (raw_offsets.get(found.start), raw_offsets.get(found.end));
+                let mut submatch = vec![("match", json::data(&line.raw[start..end]))];
+                if self.replacement.is_some() {
+                    let replaced = Value::String(self.replaced(&line.text, found.clone(), part));
+                    submatch.push(("replacement", Value::Object(vec![("text", replaced)])));
+                }
+                submatch.push(("start", Value::Number(start as u64)));
+                submatch.push(("end", Value::Number(end as u64)));
+                Value::Object(submatch)
+            })
+            .collect();
+        let data = Value::Object(vec![
+            ("path", json::data(name.as_bytes())),
+            ("lines", json::data(&line.raw)),
+            ("line_number", Value::Number(line.number as u64)),
+            ("absolute_offset", Value::Number(line.offset as u64)),
+            ("submatches", Value::Array(submatches)),
+        ]);
+        self.write_message(out, state, kind, data)
+    }
+
+    // Write a line of context in the selected output format
+    fn write_context(&self, out: &mut dyn Write, name: &str, state: &mut SearchState, line: &Line) -> io::Result<()> {
+        if self.options.json {
+            self.write_json_line(out, name, state, "context", line, &[], None)
+        } else {
+            self.write_line(out, name, line, CONTEXT_SEPARATOR, None, None, &mut state.last_printed)
+        }
+    }
+
+    // Write a whole matching or context line, separating it from the previous group if not adjacent
+    #[allow(clippy::too_many_arguments)]
+    fn write_line(
+        &self,
+        out: &mut dyn Write,
+        name: &str,
+        line: &Line,
+        separator: char,
+        match_start: Option<usize>,
+        part: Option<&MatchPart>,
+        last_printed: &mut Option<usize>,
+    ) -> io::Result<()> {
+        let has_context = self.options.before_context > 0 || self.options.after_context > 0;
+        if let (true, Some(last), Some(group_separator)) = (has_context, *last_printed, &self.options.group_separator) {
+            if line.number > last + 1 {
+                self.paint(out, |colors| &colors.separator, group_separator)?;
+                writeln!(out)?;
+            }
+        }
+        *last_printed = Some(line.number);
+
+        self.write_prefix(out, name, line.number, match_start, line.offset, separator)?;
+        self.write_text(out, line, separator == MATCH_SEPARATOR, part)?;
+        self.end_line(out)
+    }
+
+    fn end_line(&self, out: &mut dyn Write) -> io::Result<()> {
+        out.write_all(&[self.line_terminator])
+    }
+
+    // Write a selected or context line as it was read, highlighting its matches, or
+    // the part of a -U match on it, and replacing them in selected lines with --replace
+    fn write_text(&self, out: &mut dyn Write, line: &Line, selected: bool, part: Option<&MatchPart>) -> io::Result<()> {
+        let text = line.text.as_ref();
+        let raw_offsets = line.raw_offsets();
+        let raw = |range: Range<usize>| &line.raw[raw_offsets.get(range.start)..raw_offsets.get(range.end)];
+        let replacement = self.replacement.as_ref().filter(|_| selected);
+        if self.colors.is_none() && replacement.is_none() {
+            return out.write_all(raw(0..text.len()));
+        }
+        type Pick = fn(&Colors) -> &String;
+        let (line_color, match_color): (Pick, Pick) = if selected {
+            (|colors| &colors.selected_line, |colors| &colors.selected_match)
+        } else {
+            (|colors| &colors.context_line, |colors| &colors.context_match)
+        };
+
+        let matches: Vec<(Range<usize>, Option<Cow<str>>)> = match part {
+            Some(part) => {
+                let replaced = replacement.map(|_| Cow::Borrowed(part.replaced.as_deref().unwrap_or_default()));
+                vec![(part.span.clone(), replaced)]
+            }
+            None => Matcher::captures_iter(self.pattern, text)
+                .map(|captures| {
+                    let replaced = replacement.map(|template| Cow::Owned(template.expand(text, &captures)));
+                    (captures.get(0).unwrap_or_default(), replaced)
+                })
+                .collect(),
+        };
+
+        let mut last = 0;
+        for (found, replaced) in matches {
+            // Empty matches have nothing to highlight, but are still replaced
+            if found.is_empty() && replaced.is_none() {
+                continue;
+            }
+            if found.start > last {
+                self.paint_bytes(out, line_color, raw(last..found.start))?;
+            }
+            match replaced {
+                Some(replaced) => self.paint(out, match_color, replaced)?,
+                None => self.paint_bytes(out, match_color, raw(found.clone()))?,
+            }
+            last = found.end;
+        }
+        if last < text.len() {
+            self.paint_bytes(out, line_color, raw(last..text.len()))?;
+        }
+        Ok(())
+    }
+
+    // The text printed for a match, which is its replacement with --replace. The part of
+    // a -U match on a line after the one it starts on is replaced by nothing.
+    fn replaced<'t>(&self, text: &'t str, found: Range<usize>, part: Option<&'t MatchPart>) -> Cow<'t, str> {
+        if let (Some(_), Some(part)) = (&self.replacement, part) {
+            return Cow::Borrowed(part.replaced.as_deref().unwrap_or_default());
+        }
+        if let Some(template) = &self.replacement {
+            if let Some(captures) = Matcher::captures_with(self.pattern, text, found.start, MatchConfig::default()) {
+                return Cow::Owned(template.expand(text, &captures));
+            }
+        }
+        Cow::Borrowed(&text[found])
+    }
+
+    // Write `text` in the color picked from the configured colors, if any
+    fn paint(&self, out: &mut dyn Write, pick: fn(&Colors) -> &String, text: impl Display) -> io::Result<()> {
+        match &self.colors {
+            Some(colors) => colors.paint(out, pick(colors), text),
+            None => write!(out, "{}", text),
+        }
+    }
+
+    // Like `paint`, for bytes of the input
+    fn paint_bytes(&self, out: &mut dyn Write, pick: fn(&Colors) -> &String, bytes: &[u8]) -> io::Result<()> {
+        match &self.colors {
+            Some(colors) => colors.paint_bytes(out, pick(colors), bytes),
+            None => out.write_all(bytes),
+        }
+    }
+
+    // Write the file name prefix, followed by NUL instead of the separator for -Z
+    fn write_filename(&self, out: &mut dyn Write, name: &str, separator: char) -> io::Result<()> {
+        if self.with_filename {
+            self.paint(out, |colors| &colors.filename, name)?;
+            if self.options.null {
+                out.write_all(b"\0")?;
+            } else {
+                self.paint(out, |colors| &colors.separator, separator)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Write a file name listed by -l or -L, ended by NUL for -Z
+    fn write_listed_filename(&self, out: &mut dyn Write, name: &str) -> io::Result<()> {
+        self.paint(out, |colors| &colors.filename, name)?;
+        out.write_all(if self.options.null { b"\0" } else { b"\n" })
+    }
+
+    // Write the `file:line:column:offset:` fields enabled by the options
+    fn write_prefix(
+        &self,
+        out: &mut dyn Write,
+        name: &str,
+        line_number: usize,
+        match_start: Option<usize>,
+        byte_offset: usize,
+        separator: char,
+    ) -> io::Result<()> {
+        self.write_filename(out, name, separator)?;
+        if self.options.line_number {
+            self.paint(out, |colors| &colors.line_number, line_number)?;
+            self.paint(out, |colors| &colors.separator, separator)?;
+        }
+        if let (true, Some(match_start)) = (self.options.column, match_start) {
+            self.paint(out, |colors| &colors.line_number, match_start + 1)?;
+            self.paint(out, |colors| &colors.separator, separator)?;
+        }
+        if self.options.byte_offset {
+            self.paint(out, |colors| &colors.byte_offset, byte_offset)?;
+            self.paint(out, |colors| &colors.separator, separator)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use crate::matcher::Matcher;
+use crate::nfa::Program;
+use crate::parser::parse_pattern;
+use crate::Pattern;
+
+// Many patterns matched together, reporting which of them match a text
+#[derive(Clone, Debug)]
+pub struct RegexSet {
+    // Patterns without backreferences, all compiled into one program
+    program: Program,
+    compiled: Vec<usize>,
+    // The others need the backtracking matcher, and are matched one by one
+    backtracked: Vec<(usize, Pattern)>,
+    len: usize,
+}
+
+impl RegexSet {
+    pub fn new<I, S>(patterns: I) -> Result<RegexSet, String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let patterns = patterns
+            .into_iter()
+            .map(|s| parse_pattern(s.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(RegexSet::from_patterns(patterns))
+    }
+
+    pub fn from_patterns(patterns: Vec<Pattern>) -> RegexSet {
+        let len = patterns.len();
+        let mut compiled = vec![];
+        let mut compiled_patterns = vec![];
+        let mut backtracked = vec![];
+        for (index, pattern) in patterns.into_iter().enumerate() {
+            if pattern.info().has_backreferences {
+                backtracked.push((index, pattern));
+            } else {
+                compiled.push(index);
+                compiled_patterns.push(pattern);
+            }
+        }
+        RegexSet {
+            program: Program::new(&compiled_patterns),
+            compiled,
+            backtracked,
+            len,
+        }
+    }
+
+    // Indices of the patterns that match anywhere in the text, in increasing order.
+    // Like `Matcher::is_match` the text is a single line.
+    pub fn matches(&self, text: &str) -> Vec<usize> {
+        let matched = self.program.matching(text);
+        let mut indices: Vec<_> = self
+            .compiled
+            .iter()
+            .zip(matched)
+            .filter_map(|(&index, matched)| matched.then_some(index))
+            .collect();
+        indices.extend(
+            self.backtracked
+                .iter()
+                .filter(|(_, pattern)| Matcher::is_match(pattern, text))
+                .map(|(index, _)| *index),
+        );
+        indices.sort_unstable();
+        indices
+    }
+
+    // Whether any pattern matches the text
+    pub fn is_match(&self, text: &str) -> bool {
+        !self.matches(text).is_empty()
+    }
+
+    // Number of patterns in the set
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
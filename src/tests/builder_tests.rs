@@ -0,0 +1,61 @@
+use crate::parser::parse_pattern;
+use crate::{BuildError, Matcher, Pat, Pattern};
+
+fn regex(pat: Pat) -> String {
+    pat.build().unwrap().to_string()
+}
+
+#[test]
+fn test_build_patterns() {
+    let pattern = Pat::lit("foo").then(Pat::digit().repeat(2..=4)).group().build().unwrap();
+    assert_eq!(pattern.to_string(), "(foo\\d{2,4})");
+    assert_eq!(Matcher::find(&pattern, "a foo1234"), Some(2..9));
+    assert_eq!(regex(Pat::start_of_line().then(Pat::word().one_or_more()).then(Pat::end_of_line())), "^\\w+$");
+    assert_eq!(regex(Pat::any_of([Pat::lit("a.b"), Pat::char('c'), Pat::not_set("xy")])), "a\\.b|c|[^xy]");
+    assert_eq!(regex(Pat::any().zero_or_more().then(Pat::space().optional())), ".*\\s?");
+    assert_eq!(regex(Pat::char('a').repeat(3..)), "a{3,}");
+    assert_eq!(regex(Pat::char('a').repeat(..3)), "a{0,2}");
+}
+
+#[test]
+fn test_build_groups() {
+    let year = Pat::digit().repeat(4..=4).named("year");
+    let pattern = year.then(Pat::char('-').group()).then(Pat::backref(2)).build().unwrap();
+    assert_eq!(pattern.capture_index("year"), Some(1));
+    assert_eq!(pattern.to_string(), "(?<year>\\d{4})(-)\\2");
+    // A group containing groups is nested, as the parser makes it
+    let nested = Pat::char('a').group().then(Pat::char('b')).group().build().unwrap();
+    assert_eq!(nested, parse_pattern("((a)b)").unwrap());
+    let parsed = Pat::from(parse_pattern("(x)").unwrap()).then(Pat::backref(1)).build().unwrap();
+    assert!(Matcher::is_match(&parsed, "xx"));
+    // A parsed choice doesn't capture, so it takes no group number
+    let choice = Pat::from(parse_pattern("x|y").unwrap()).then(Pat::char('c').group()).then(Pat::backref(1));
+    let choice = choice.build().unwrap();
+    assert_eq!(choice.to_string(), "(?:x|y)(c)\\1");
+    assert!(Matcher::is_match(&choice, "xcc"));
+}
+
+#[test]
+fn test_build_errors() {
+    assert_eq!(Pat::char('a').group().then(Pat::backref(2)).build(), Err(BuildError::MissingGroup(2)));
+    assert_eq!(Pat::backref(0).build(), Err(BuildError::MissingGroup(0)));
+    // A backreference can't refer to a group that starts after it
+    assert_eq!(Pat::backref(1).then(Pat::lit("a").group()).build(), Err(BuildError::LaterGroup(1)));
+    assert_eq!(Pat::any_of([Pat::backref(1), Pat::lit("a").group()]).build(), Err(BuildError::LaterGroup(1)));
+    let (min, max) = (3, 2);
+    assert_eq!(Pat::char('a').repeat(min..=max).build(), Err(BuildError::EmptyRange));
+    assert_eq!(Pat::char('a').repeat(..0).then(Pat::lit("b")).build(), Err(BuildError::EmptyRange));
+    assert_eq!(Pat::any_of([]).build(), Err(BuildError::EmptyChoice));
+    assert_eq!(Pat::any().named("1st").build(), Err(BuildError::InvalidName("1st".to_string())));
+    let twice = Pat::any().named("x").then(Pat::any().named("x"));
+    assert_eq!(twice.build(), Err(BuildError::DuplicateName("x".to_string())));
+    assert_eq!(BuildError::MissingGroup(3).to_string(), "backreference \\3 refers to a group that doesn't exist");
+    assert_eq!(BuildError::LaterGroup(1).to_string(), "backreference \\1 comes before the group it refers to");
+    assert_eq!(Pat::lit("").build(), Ok(Pattern::Sequence(vec![])));
+    // Sets are written as the parser reads them back, or rejected
+    assert_eq!(regex(Pat::set("^a")), "[a^]");
+    assert_eq!(regex(Pat::not_set("^a")), "[^^a]");
+    assert_eq!(Pat::set("]a").build(), Err(BuildError::InvalidSet("]a".to_string())));
+    assert_eq!(Pat::not_set("]").build(), Err(BuildError::InvalidSet("]".to_string())));
+    assert_eq!(Pat::set("^").or(Pat::char('a')).build(), Err(BuildError::InvalidSet("^".to_string())));
+}
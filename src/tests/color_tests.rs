@@ -0,0 +1,36 @@
+use crate::color::{ColorChoice, Colors};
+
+#[test]
+fn test_parse_grep_colors() {
+    let colors = Colors::parse("ms=04;32:fn=34:ne");
+    assert_eq!(colors.selected_match, "04;32");
+    assert_eq!(colors.context_match, "01;31");
+    assert_eq!(colors.filename, "34");
+    assert_eq!(colors.line_number, "32");
+    assert!(!colors.erase_line);
+
+    let colors = Colors::parse("mt=01;33:sl=1:unknown=5:rv");
+    assert_eq!(colors.selected_match, "01;33");
+    assert_eq!(colors.context_match, "01;33");
+    assert_eq!(colors.selected_line, "1");
+    assert!(colors.erase_line);
+}
+
+#[test]
+fn test_paint() {
+    let mut out = Vec::new();
+    Colors::default().paint(&mut out, "35", "file").unwrap();
+    assert_eq!(out, b"\x1b[35m\x1b[Kfile\x1b[m\x1b[K");
+
+    let mut out = Vec::new();
+    Colors::parse("ne").paint(&mut out, "", "plain").unwrap();
+    assert_eq!(out, b"plain");
+}
+
+#[test]
+fn test_parse_color_choice() {
+    assert_eq!("auto".parse(), Ok(ColorChoice::Auto));
+    assert_eq!("always".parse(), Ok(ColorChoice::Always));
+    assert_eq!("never".parse(), Ok(ColorChoice::Never));
+    assert!("sometimes".parse::<ColorChoice>().is_err());
+}
@@ -0,0 +1,73 @@
+use crate::parser::parse_pattern;
+use crate::{escape, Matcher, Pattern};
+
+// Parse, print and parse again, returning the printed regex
+fn round_trip(regex: &str) -> String {
+    let pattern = parse_pattern(regex).unwrap();
+    let printed = pattern.to_string();
+    assert_eq!(parse_pattern(&printed), Ok(pattern), "{:?} printed as {:?}", regex, printed);
+    printed
+}
+
+#[test]
+fn test_display_canonical() {
+    assert_eq!(round_trip("abc"), "abc");
+    assert_eq!(round_trip("^[0123456789]+\\s$"), "^\\d+\\s$");
+    assert_eq!(round_trip("a{0,}b{1,}c{2}d{2,3}"), "a*b{1,}c{2}d{2,3}");
+    assert_eq!(round_trip("cat|dog"), "cat|dog");
+    assert_eq!(round_trip("(cat|dog) and \\1"), "(cat|dog) and \\1");
+    assert_eq!(round_trip("(?:ab)+(?P<x>c)"), "(?:ab)+(?<x>c)");
+    assert_eq!(round_trip("(?s)."), "[^]");
+    assert_eq!(round_trip("a**"), "(?:a*)*");
+}
+
+#[test]
+fn test_display_escapes() {
+    assert_eq!(round_trip("\\.\\*\\(\\)\\[\\]\\{\\}\\|\\?\\+\\\\"), "\\.\\*\\(\\)\\[\\]\\{\\}\\|\\?\\+\\\\");
+    assert_eq!(round_trip("a^b$c"), "a\\^b\\$c");
+    assert_eq!(round_trip("\\n\t"), "\\n\\t");
+    assert_eq!(round_trip("[^a.*]"), "[^a.*]");
+}
+
+#[test]
+fn test_escape() {
+    assert_eq!(escape("a.b*c"), "a\\.b\\*c");
+    assert_eq!(escape("^(x|y)$"), "\\^\\(x\\|y\\)\\$");
+    assert_eq!(escape(""), "");
+    for text in ["1+1=2?", "[a]{2}\\", "tab\tnewline\n", "$", "é"] {
+        let pattern = parse_pattern(&escape(text)).unwrap();
+        assert_eq!(pattern.clone().normalize(), Pattern::literal(text), "{:?}", text);
+        assert_eq!(Matcher::find(&pattern, &format!("x{}", text)), Some(1..1 + text.len()));
+    }
+}
+
+#[test]
+fn test_display_round_trip() {
+    let regexes = [
+        "",
+        "^$",
+        "a|^b$|(c|d)",
+        "((a)(b(c)))\\3\\4",
+        "(^a$)|(?:^)x(?:$)",
+        "(?:a|b)*c?",
+        "[abc]\\w\\d\\D\\S.",
+        "x(?:y(?:z))",
+        "\\b{start-half}word\\b{end-half}",
+        "(a|b)(?<name>(c)|d){2,}",
+    ];
+    for regex in regexes {
+        round_trip(regex);
+    }
+}
+
+#[test]
+fn test_display_built_patterns() {
+    let patterns = [
+        Pattern::any_of(vec![parse_pattern("a|b").unwrap(), parse_pattern("(c)\\1").unwrap()]),
+        parse_pattern("foo|bar").unwrap().whole_word(),
+        parse_pattern("x").unwrap().whole_line(),
+    ];
+    for pattern in patterns {
+        assert_eq!(parse_pattern(&pattern.to_string()), Ok(pattern.clone()), "{}", pattern);
+    }
+}
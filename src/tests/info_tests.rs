@@ -0,0 +1,65 @@
+use crate::parser::parse_pattern;
+use crate::PatternInfo;
+
+fn info(regex: &str) -> PatternInfo {
+    parse_pattern(regex).unwrap().info()
+}
+
+#[test]
+fn test_info_lengths() {
+    let lengths = |regex| {
+        let info = info(regex);
+        (info.min_len, info.max_len)
+    };
+    assert_eq!(lengths("abc"), (3, Some(3)));
+    assert_eq!(lengths("ab?c{2,4}"), (3, Some(6)));
+    assert_eq!(lengths("a+|xyz"), (1, None));
+    assert_eq!(lengths("^(ab|c)\\1$"), (2, Some(4)));
+    assert_eq!(lengths(""), (0, Some(0)));
+    // Normalization doesn't change what can match
+    let pattern = parse_pattern("(?:ab|ac)d{2}").unwrap();
+    assert_eq!(pattern.info().max_len, pattern.normalize().info().max_len);
+}
+
+#[test]
+fn test_info_anchors() {
+    let anchors = |regex| {
+        let info = info(regex);
+        (info.anchored_start, info.anchored_end)
+    };
+    assert_eq!(anchors("^abc$"), (true, true));
+    assert_eq!(anchors("^a|^b"), (true, false));
+    assert_eq!(anchors("^a|b$"), (false, false));
+    assert_eq!(anchors("(^a)+b"), (true, false));
+    assert_eq!(anchors("a(?:^)?"), (false, false));
+}
+
+#[test]
+fn test_info_empty_matches() {
+    let empty = |regex| {
+        let info = info(regex);
+        (info.matches_empty, info.matches_every_line)
+    };
+    assert_eq!(empty("a"), (false, false));
+    assert_eq!(empty("a*"), (true, true));
+    assert_eq!(empty("^"), (true, true));
+    assert_eq!(empty("x?$"), (true, true));
+    // Only empty lines match these
+    assert_eq!(empty("^$"), (true, false));
+    assert_eq!(empty("\\b{start-half}\\b{end-half}"), (true, false));
+    assert_eq!(empty("a|^b*"), (true, true));
+}
+
+#[test]
+fn test_info_groups() {
+    let info = info("(?<year>\\d+)-(\\d+)(x|y)\\2");
+    assert_eq!(info.group_count, 3);
+    assert_eq!(info.group_names, vec![Some("year".to_string()), None, None]);
+    assert!(info.has_backreferences);
+    assert!(!self::info("a(b)|c").has_backreferences);
+    assert_eq!(self::info("a|b").group_count, 0);
+    // Parentheses around the whole pattern still make a group
+    let info = self::info("(a|b)");
+    assert_eq!(info.group_count, 1);
+    assert_eq!(info.group_names, vec![None]);
+}
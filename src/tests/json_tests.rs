@@ -0,0 +1,158 @@
+use crate::json::{self, Value};
+use crate::options::Options;
+use crate::parse_patterns;
+use crate::parser::parse_pattern;
+use std::borrow::Cow;
+use std::time::Duration;
+
+fn to_string(value: &Value) -> String {
+    let mut out = Vec::new();
+    value.write(&mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_write_values() {
+    let value = Value::Object(vec![
+        ("a", Value::Array(vec![Value::Number(1), Value::Null])),
+        ("b", Value::String(Cow::Borrowed("q\"\\\n\t\x01é"))),
+    ]);
+    assert_eq!(to_string(&value), r#"{"a":[1,null],"b":"q\"\\\n\t\u0001é"}"#);
+}
+
+#[test]
+fn test_data() {
+    assert_eq!(to_string(&json::data(b"foo\n")), r#"{"text":"foo\n"}"#);
+    assert_eq!(to_string(&json::data(b"\xff\xfeA")), r#"{"bytes":"//5B"}"#);
+    assert_eq!(
+        to_string(&json::duration(Duration::from_micros(1_500_001))),
+        r#"{"secs":1,"nanos":500001000,"human":"1.500001s"}"#
+    );
+}
+
+#[test]
+fn test_base64() {
+    assert_eq!(json::base64(b""), "");
+    assert_eq!(json::base64(b"f"), "Zg==");
+    assert_eq!(json::base64(b"fo"), "Zm8=");
+    assert_eq!(json::base64(b"foo"), "Zm9v");
+    assert_eq!(json::base64(b"foob"), "Zm9vYg==");
+}
+
+fn ast(regex: &str) -> String {
+    to_string(&json::pattern(&parse_pattern(regex).unwrap()))
+}
+
+#[test]
+fn test_pattern_schema() {
+    assert_eq!(ast("a"), r#"{"exact_char":"a"}"#);
+    assert_eq!(ast("^."), r#"{"sequence":["start_of_line","any_char"]}"#);
+    assert_eq!(
+        ast("[^ab]{2,}"),
+        r#"{"repeated":{"min":2,"max":null,"pattern":{"character_set":{"chars":"ab","negated":true}}}}"#
+    );
+    assert_eq!(
+        ast("(?<x>a|b)\\1|c?"),
+        concat!(
+            r#"{"one_of":[{"sequence":[{"named_capture":{"name":"x","pattern":{"one_of":"#,
+            r#"[{"exact_char":"a"},{"exact_char":"b"}]}}},{"backreference":1}]},{"zero_or_one":{"exact_char":"c"}}]}"#
+        )
+    );
+    let pattern = parse_pattern("ab").unwrap().normalize();
+    assert_eq!(to_string(&json::pattern(&pattern)), r#"{"literal":"ab"}"#);
+}
+
+#[test]
+fn test_dumped_pattern() {
+    // --dump-ast shows the patterns as written, before -w and normalization
+    let options = Options::parse(["-w", "-e", "ab", "-e", "c"].iter().map(|s| s.to_string())).unwrap();
+    assert_eq!(
+        to_string(&json::pattern(&parse_patterns(&options).unwrap())),
+        r#"{"one_of":[{"sequence":[{"exact_char":"a"},{"exact_char":"b"}]},{"exact_char":"c"}]}"#
+    );
+}
+
+// The hand-written schema is the serde one for every variant
+#[cfg(feature = "serde")]
+#[test]
+fn test_pattern_serde_variants() {
+    use crate::Pattern;
+
+    let boxed = |pattern: Pattern| Box::new(pattern);
+    let char_set = |negated| Pattern::CharacterSet { chars: "a\"b".to_string(), negated };
+    let variants = vec![
+        Pattern::ExactChar('\n'),
+        Pattern::Literal("ab".to_string()),
+        Pattern::AnyChar,
+        Pattern::AlphaNumeric,
+        Pattern::Sequence(vec![]),
+        Pattern::Repeated { min: 2, max: None, pattern: boxed(Pattern::AnyChar) },
+        Pattern::Repeated { min: 0, max: Some(3), pattern: boxed(Pattern::AnyChar) },
+        Pattern::OneOf(vec![Pattern::AnyChar, Pattern::AlphaNumeric]),
+        char_set(false),
+        char_set(true),
+        Pattern::StartOfLine,
+        Pattern::EndOfLine,
+        Pattern::NoWordBefore,
+        Pattern::NoWordAfter,
+        Pattern::OneOrMore(boxed(Pattern::AnyChar)),
+        Pattern::ZeroOrOne(boxed(Pattern::AnyChar)),
+        Pattern::Alternation(vec![Pattern::AnyChar]),
+        Pattern::Backreference(1),
+        Pattern::CaptureGroup(boxed(Pattern::AnyChar)),
+        Pattern::NestedCapture(boxed(Pattern::CaptureGroup(boxed(Pattern::AnyChar)))),
+        Pattern::NamedCapture { name: "x".to_string(), pattern: boxed(Pattern::AnyChar) },
+    ];
+    // A variant added to Pattern has to be numbered here and added above
+    let number = |pattern: &Pattern| match pattern {
+        Pattern::ExactChar(_) => 0,
+        Pattern::Literal(_) => 1,
+        Pattern::AnyChar => 2,
+        Pattern::AlphaNumeric => 3,
+        Pattern::Sequence(_) => 4,
+        Pattern::Repeated { .. } => 5,
+        Pattern::OneOf(_) => 6,
+        Pattern::CharacterSet { .. } => 7,
+        Pattern::StartOfLine => 8,
+        Pattern::EndOfLine => 9,
+        Pattern::NoWordBefore => 10,
+        Pattern::NoWordAfter => 11,
+        Pattern::OneOrMore(_) => 12,
+        Pattern::ZeroOrOne(_) => 13,
+        Pattern::Alternation(_) => 14,
+        Pattern::Backreference(_) => 15,
+        Pattern::CaptureGroup(_) => 16,
+        Pattern::NestedCapture(_) => 17,
+        Pattern::NamedCapture { .. } => 18,
+    };
+    assert!((0..=18).all(|n| variants.iter().any(|variant| number(variant) == n)));
+    for variant in &variants {
+        let written: serde_json::Value = serde_json::from_str(&to_string(&json::pattern(variant))).unwrap();
+        assert_eq!(written, serde_json::to_value(variant).unwrap(), "{:?}", variant);
+    }
+}
+
+// The serde serialization follows the same schema
+#[cfg(feature = "serde")]
+#[test]
+fn test_pattern_serde() {
+    use crate::{MatchConfig, Pattern};
+
+    let regexes = ["", "a\\nb", "^(a+|(b)*)\\2$", "(?<n>\\d{1,3})[x]?\\b{end-half}", "(?:ab|cd).+"];
+    for regex in regexes {
+        let pattern = parse_pattern(regex).unwrap();
+        for pattern in [pattern.clone(), pattern.normalize()] {
+            let serialized = serde_json::to_string(&pattern).unwrap();
+            assert_eq!(serialized, to_string(&json::pattern(&pattern)), "{:?}", regex);
+            assert_eq!(serde_json::from_str::<Pattern>(&serialized).unwrap(), pattern);
+        }
+    }
+    let config = MatchConfig {
+        line_terminator: Some('\n'),
+        crlf: true,
+        multiline: false,
+    };
+    let serialized = serde_json::to_string(&config).unwrap();
+    assert_eq!(serialized, r#"{"line_terminator":"\n","crlf":true,"multiline":false}"#);
+    assert_eq!(serde_json::from_str::<MatchConfig>(&serialized).unwrap(), config);
+}
@@ -1,5 +1,5 @@
 use crate::Pattern;
-use crate::matcher::Matcher;
+use crate::matcher::{MatchConfig, Matcher};
 use crate::parser::parse_pattern;
 
 #[test]
@@ -54,7 +54,7 @@ fn test_alpha_numeric() {
     assert!(Matcher::match_str(&Pattern::AlphaNumeric, "a123"));
     assert!(Matcher::match_str(&Pattern::AlphaNumeric, "_abc"));
     assert!(Matcher::match_str(&Pattern::AlphaNumeric, "9xyz"));
-    assert!(!Matcher::match_str(&Pattern::AlphaNumeric, "!abc"));
+    assert!(!Matcher::match_str(&Pattern::AlphaNumeric, "!#%"));
 }
 
 #[test]
@@ -84,7 +84,7 @@ fn test_character_set() {
         negated: true,
     };
     assert!(Matcher::match_str(&negated_pattern, "xyz"));
-    assert!(!Matcher::match_str(&negated_pattern, "apple"));
+    assert!(!Matcher::match_str(&negated_pattern, "aeiou"));
 }
 
 #[test]
@@ -119,7 +119,7 @@ fn test_one_or_more() {
 fn test_zero_or_one() {
     assert!(Matcher::match_str(&parse_pattern("dogs?").unwrap(), "dogs"));
     assert!(Matcher::match_str(&parse_pattern("dogs?").unwrap(), "dog"));
-    assert!(!Matcher::match_str(&parse_pattern("dogs?").unwrap(), "dogss"));
+    assert!(!Matcher::match_str(&parse_pattern("dogs?").unwrap(), "dots"));
     assert!(!Matcher::match_str(&parse_pattern("dogs?").unwrap(), "cat"));
     assert!(Matcher::match_str(&parse_pattern("colou?r").unwrap(), "color"));
     assert!(Matcher::match_str(&parse_pattern("colou?r").unwrap(), "colour"));
@@ -135,6 +135,24 @@ fn test_alternation() {
     assert!(!Matcher::match_str(&parse_pattern("a(b|c)d").unwrap(), "ad"));
 }
 
+#[test]
+fn test_alternation_groups() {
+    // A parenthesized alternation is a group wherever it appears
+    let captures = Matcher::captures(&parse_pattern("(a|b)").unwrap(), "xb").unwrap();
+    assert_eq!((captures.len(), captures.get(1)), (2, Some(1..2)));
+    let pattern = parse_pattern("((a|b))\\2").unwrap();
+    assert_eq!(Matcher::find(&pattern, "xbb"), Some(1..3));
+    assert!(!Matcher::is_match(&pattern, "ab"));
+    let pattern = parse_pattern("(?:(a|b))\\1").unwrap();
+    assert_eq!(Matcher::find(&pattern, "xaa"), Some(1..3));
+    assert_eq!(Matcher::captures(&pattern, "bb").unwrap().get(1), Some(0..1));
+    // An Alternation built by hand is a choice like OneOf, without a group
+    let choice = Pattern::Alternation(vec![Pattern::ExactChar('a'), Pattern::ExactChar('b')]);
+    assert_eq!(Matcher::captures(&choice, "xb").unwrap().len(), 1);
+    assert_eq!(choice.to_string(), "a|b");
+    assert_eq!(choice.normalize(), parse_pattern("a|b").unwrap().normalize());
+}
+
 #[test]
 fn test_backreferences() {
     assert!(Matcher::match_str(&parse_pattern("(cat) and \\1").unwrap(), "cat and cat"));
@@ -166,4 +184,93 @@ fn test_nested_backreferences() {
         "'cat and cat' is the same as 'cat and cat'"));
     assert!(!Matcher::match_str(&parse_pattern("('(cat) and \\2') is the same as \\1").unwrap(),
         "'cat and dog' is the same as 'cat and dog'"));
-}
\ No newline at end of file
+}
+#[test]
+fn test_is_match_line() {
+    assert!(Matcher::is_match(&parse_pattern("^$").unwrap(), ""));
+    assert!(!Matcher::is_match(&parse_pattern("^$").unwrap(), "a"));
+    assert!(Matcher::is_match(&parse_pattern("(c.t|d.g) and (f..h|b..d), \\1 with \\2").unwrap(),
+        "cat and fish, cat with fish"));
+    assert!(!Matcher::is_match(&parse_pattern("(c.t|d.g) and (f..h|b..d), \\1 with \\2").unwrap(),
+        "cat and fish, dog with fish"));
+}
+
+#[test]
+fn test_find() {
+    assert_eq!(Matcher::find(&parse_pattern("o+").unwrap(), "foo bar"), Some(1..3));
+    assert_eq!(Matcher::find(&parse_pattern("\\d+$").unwrap(), "a1 b22"), Some(4..6));
+    assert_eq!(Matcher::find(&parse_pattern("x").unwrap(), "foo"), None);
+    assert_eq!(Matcher::find_at(&parse_pattern("o").unwrap(), "foo", 2), Some(2..3));
+}
+
+#[test]
+fn test_find_iter() {
+    let matches: Vec<_> = Matcher::find_iter(&parse_pattern("\\d+").unwrap(), "a1 b22 c333").collect();
+    assert_eq!(matches, vec![1..2, 4..6, 8..11]);
+    let empty: Vec<_> = Matcher::find_iter(&parse_pattern("x*").unwrap(), "axb").collect();
+    assert_eq!(empty, vec![0..0, 1..2, 3..3]);
+}
+
+#[test]
+fn test_find_with_line_terminator() {
+    let config = MatchConfig {
+        line_terminator: Some('\n'),
+        ..MatchConfig::default()
+    };
+    let text = "foo\nbar\nbaz";
+    assert_eq!(Matcher::find_with(&parse_pattern("^ba").unwrap(), text, 0, config), Some(4..6));
+    assert_eq!(Matcher::find_with(&parse_pattern("o$").unwrap(), text, 0, config), Some(2..3));
+    assert_eq!(Matcher::find_with(&parse_pattern("o.b").unwrap(), text, 0, config), None);
+    assert_eq!(Matcher::find_with(&parse_pattern("[^x]+").unwrap(), text, 0, config), Some(0..3));
+    assert_eq!(Matcher::find_with(&parse_pattern("^b").unwrap(), text, 0, MatchConfig::default()), None);
+}
+
+#[test]
+fn test_match_records() {
+    let pattern = parse_pattern("^b.r$").unwrap();
+    assert!(Matcher::match_records(&pattern, "foo\0bar\0", '\0'));
+    assert!(!Matcher::match_records(&pattern, "foo\nbar\0", '\0'));
+    assert!(Matcher::match_str(&pattern, "foo\nbar\n"));
+}
+
+#[test]
+fn test_find_crlf() {
+    let config = MatchConfig {
+        line_terminator: Some('\n'),
+        crlf: true,
+        ..MatchConfig::default()
+    };
+    let pattern = parse_pattern("o.$").unwrap();
+    assert_eq!(Matcher::find_with(&pattern, "foo\r\nboo\r", 0, config), Some(1..3));
+    assert_eq!(Matcher::find_with(&pattern, "foo\r\nboo\r", 3, config), Some(6..8));
+    // A carriage return inside a line is ordinary text
+    assert_eq!(Matcher::find_with(&parse_pattern("a.b").unwrap(), "a\rb\r\n", 0, config), Some(0..3));
+    assert_eq!(Matcher::find(&pattern, "foo\r"), Some(2..4));
+}
+
+#[test]
+fn test_find_multiline() {
+    let config = MatchConfig {
+        line_terminator: Some('\n'),
+        multiline: true,
+        ..MatchConfig::default()
+    };
+    let text = "ab\ncd\n";
+    assert_eq!(Matcher::find_with(&parse_pattern("b\\sc").unwrap(), text, 0, config), Some(1..4));
+    assert_eq!(Matcher::find_with(&parse_pattern("b.c").unwrap(), text, 0, config), None);
+    assert_eq!(Matcher::find_with(&parse_pattern("(?s)b.c").unwrap(), text, 0, config), Some(1..4));
+}
+
+#[test]
+fn test_captures() {
+    let pattern = parse_pattern("(a)(x)?|(b)").unwrap();
+    let captures = Matcher::captures(&pattern, "zb").unwrap();
+    assert_eq!(captures.len(), 4);
+    assert_eq!(captures.get(0), Some(1..2));
+    assert_eq!(captures.get(1), None);
+    assert_eq!(captures.get(3), Some(1..2));
+    assert_eq!(captures.get(4), None);
+
+    let spans: Vec<_> = Matcher::captures_iter(&pattern, "ab a").map(|c| c.get(1)).collect();
+    assert_eq!(spans, vec![Some(0..1), None, Some(3..4)]);
+}
@@ -1,3 +0,0 @@
-mod pattern_tests;
-mod parser_tests;
-mod matcher_tests;
\ No newline at end of file
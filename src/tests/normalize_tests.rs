@@ -0,0 +1,123 @@
+use crate::parser::parse_pattern;
+use crate::{Matcher, Pattern};
+
+fn normalize(regex: &str) -> Pattern {
+    parse_pattern(regex).unwrap().normalize()
+}
+
+fn literal(s: &str) -> Pattern {
+    Pattern::Literal(s.to_string())
+}
+
+fn set(chars: &str) -> Pattern {
+    Pattern::CharacterSet { chars: chars.to_string(), negated: false }
+}
+
+fn repeated(min: usize, max: Option<usize>, pattern: Pattern) -> Pattern {
+    Pattern::Repeated { min, max, pattern: Box::new(pattern) }
+}
+
+#[test]
+fn test_normalize_literals() {
+    assert_eq!(normalize("abc"), literal("abc"));
+    assert_eq!(normalize("ab+c"), Pattern::Sequence(vec![Pattern::ExactChar('a'), repeated(1, None, Pattern::ExactChar('b')), Pattern::ExactChar('c')]));
+    assert_eq!(normalize("(?:ab)*"), repeated(0, None, literal("ab")));
+}
+
+#[test]
+fn test_normalize_variants() {
+    assert_eq!(normalize("a?"), repeated(0, Some(1), Pattern::ExactChar('a')));
+    assert_eq!(normalize("((a)b)"), normalize("((a)b)").normalize());
+    assert_eq!(
+        normalize("((a)b)"),
+        Pattern::CaptureGroup(Box::new(Pattern::Sequence(vec![Pattern::CaptureGroup(Box::new(Pattern::ExactChar('a'))), Pattern::ExactChar('b')])))
+    );
+    // Parentheses around a choice make a group, at the root as anywhere else
+    assert_eq!(normalize("ab|cd"), Pattern::OneOf(vec![literal("ab"), literal("cd")]));
+    assert_eq!(normalize("(ab|cd)"), Pattern::CaptureGroup(Box::new(Pattern::OneOf(vec![literal("ab"), literal("cd")]))));
+    assert_eq!(
+        normalize("x(ab|cd)"),
+        Pattern::Sequence(vec![Pattern::ExactChar('x'), Pattern::CaptureGroup(Box::new(Pattern::OneOf(vec![literal("ab"), literal("cd")])))])
+    );
+}
+
+#[test]
+fn test_normalize_factors_prefixes() {
+    assert_eq!(normalize("abc|abd"), Pattern::Sequence(vec![literal("ab"), set("cd")]));
+    assert_eq!(
+        normalize("foo|foobar|x"),
+        Pattern::OneOf(vec![
+            Pattern::Sequence(vec![literal("foo"), Pattern::OneOf(vec![Pattern::Sequence(vec![]), literal("bar")])]),
+            Pattern::ExactChar('x'),
+        ])
+    );
+    // Branches that aren't neighbours keep their order
+    assert_eq!(normalize("ab|c|ad"), Pattern::OneOf(vec![literal("ab"), Pattern::ExactChar('c'), literal("ad")]));
+    // Groups stay in place
+    assert_eq!(normalize("(a)b|(a)c").to_string(), "(a)b|(a)c");
+    // So do prefixes that can match different lengths
+    assert_eq!(normalize("a*a|a*c").to_string(), "a*a|a*c");
+    assert_eq!(normalize("(.+(.)|.+)").to_string(), "(.{1,}(.)|.{1,})");
+}
+
+#[test]
+fn test_normalize_folds_repetitions() {
+    assert_eq!(normalize("(?:a*)*"), repeated(0, None, Pattern::ExactChar('a')));
+    assert_eq!(normalize("(?:a+)?"), repeated(0, None, Pattern::ExactChar('a')));
+    assert_eq!(normalize("(?:a{2,3}){2}"), repeated(4, Some(6), Pattern::ExactChar('a')));
+    assert_eq!(normalize("(?:ab){1}"), literal("ab"));
+    // Counts with gaps, or groups, are left nested
+    assert_eq!(normalize("(?:a{2}){1,2}").to_string(), "(?:a{2}){1,2}");
+    assert_eq!(normalize("(a*)*").to_string(), "(a*)*");
+}
+
+#[test]
+fn test_normalize_classes() {
+    assert_eq!(normalize("a|b|[bc]"), set("abc"));
+    assert_eq!(normalize("x(?:\\d|a)"), Pattern::Sequence(vec![Pattern::ExactChar('x'), set("0123456789a")]));
+    assert_eq!(normalize("a|b|a"), set("ab"));
+    // Sets are ordered to print as the same set, or left as choices where they can't be
+    assert_eq!(normalize("\\^|a"), set("a^"));
+    assert_eq!(normalize("\\^|a").to_string(), "[a^]");
+    assert_eq!(normalize("\\]|a").to_string(), "\\]|a");
+    assert_eq!(normalize("\\^|\\^").to_string(), "\\^");
+}
+
+#[test]
+fn test_normalize_keeps_matches() {
+    let regexes = ["(cat|dog) and \\1", "abc|abd|ab", "(?:a*)*b", "x(?:y|yz)z", "^(a|b)+$", "(\\w+) \\1|foo"];
+    let texts = ["cat and cat", "dog and cat", "abd", "ab", "aab", "xyzz", "xyz", "abba", "the the", "food"];
+    for regex in regexes {
+        let pattern = parse_pattern(regex).unwrap();
+        let normalized = pattern.clone().normalize();
+        for text in texts {
+            assert_eq!(Matcher::captures(&normalized, text), Matcher::captures(&pattern, text), "{:?} on {:?}", regex, text);
+        }
+    }
+}
+
+#[test]
+fn test_normalize_keeps_captures() {
+    // Every text of up to four characters, over the characters the pieces use
+    let mut texts = vec![String::new()];
+    for len in 1..=4 {
+        let shorter: Vec<_> = texts.iter().filter(|text| text.len() == len - 1).cloned().collect();
+        texts.extend(shorter.iter().flat_map(|text| ['a', 'b', 'c'].map(|c| format!("{}{}", text, c))));
+    }
+    let pieces = ["a", "c", "ab", "a*", "a+", "a?", ".+", "(.)", "(a)", "[ab]", "(?:a|ab)", "(a*)"];
+    for x in pieces {
+        for y in pieces {
+            for regex in [format!("{x}{y}|{x}c"), format!("({x}|{x}{y})b"), format!("{x}|{y}|{x}{y}\\1?")] {
+                let pattern = match parse_pattern(&regex) {
+                    Ok(pattern) => pattern,
+                    // A backreference without a group
+                    Err(_) => continue,
+                };
+                let normalized = pattern.clone().normalize();
+                for text in &texts {
+                    assert_eq!(Matcher::captures(&normalized, text), Matcher::captures(&pattern, text), "{:?} on {:?}", regex, text);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::color::ColorChoice;
+use crate::options::{AstFormat, BinaryFiles, Options, OutputMode};
+
+fn parse(args: &[&str]) -> Options {
+    Options::parse(args.iter().map(|s| s.to_string())).unwrap()
+}
+
+fn parse_error(args: &[&str]) -> String {
+    Options::parse(args.iter().map(|s| s.to_string())).unwrap_err().to_string()
+}
+
+#[test]
+fn test_parse_operands() {
+    let options = parse(&["-E", "foo", "a.txt", "b.txt"]);
+    assert_eq!(options.patterns, vec!["foo"]);
+    assert_eq!(options.files, vec!["a.txt", "b.txt"]);
+    // Options may follow operands
+    let options = parse(&["foo", "a.txt", "-c"]);
+    assert_eq!(options.files, vec!["a.txt"]);
+    assert_eq!(options.output, OutputMode::Count);
+    // With -e the first operand is an input file rather than the pattern
+    assert_eq!(parse(&["-e", "a", "b"]).files, vec!["b"]);
+    assert_eq!(parse(&["foo", "-"]).files, vec!["-"]);
+}
+
+#[test]
+fn test_parse_combined_short_flags() {
+    let options = parse(&["-nvc", "foo"]);
+    assert!(options.line_number);
+    assert!(options.invert_match);
+    assert_eq!(options.output, OutputMode::Count);
+
+    let options = parse(&["-nA3", "-efoo", "-m", "2"]);
+    assert!(options.line_number);
+    assert_eq!(options.after_context, 3);
+    assert_eq!(options.patterns, vec!["foo"]);
+    assert_eq!(options.max_count, Some(2));
+}
+
+#[test]
+fn test_parse_fixed_strings() {
+    assert!(parse(&["-F", "a.b"]).fixed_strings);
+    assert!(parse(&["--fixed-strings", "-E", "-F", "a"]).fixed_strings);
+    assert!(!parse(&["-F", "-E", "a"]).fixed_strings);
+}
+
+#[test]
+fn test_parse_long_options() {
+    let options = parse(&["--regexp=foo", "--regexp", "bar", "--max-count=1", "--color=always", "--count"]);
+    assert_eq!(options.patterns, vec!["foo", "bar"]);
+    assert_eq!(options.max_count, Some(1));
+    assert_eq!(options.color, ColorChoice::Always);
+    assert_eq!(options.output, OutputMode::Count);
+
+    assert_eq!(parse(&["--colour", "foo"]).color, ColorChoice::Auto);
+    assert_eq!(parse(&["--group-separator=", "foo"]).group_separator, Some(String::new()));
+}
+
+#[test]
+fn test_parse_end_of_options() {
+    let options = parse(&["--", "-v", "-c"]);
+    assert_eq!(options.patterns, vec!["-v"]);
+    assert_eq!(options.files, vec!["-c"]);
+    assert!(!options.invert_match);
+}
+
+#[test]
+fn test_parse_context() {
+    let options = parse(&["-5", "foo"]);
+    assert_eq!((options.before_context, options.after_context), (5, 5));
+    let options = parse(&["-n12", "foo"]);
+    assert!(options.line_number);
+    assert_eq!(options.after_context, 12);
+    let options = parse(&["-A", "1", "--context=4", "foo"]);
+    assert_eq!((options.before_context, options.after_context), (4, 1));
+}
+
+#[test]
+fn test_parse_help_and_version() {
+    assert!(parse(&["--help"]).show_help);
+    assert!(parse(&["-V"]).show_version);
+    assert!(parse(&["--version"]).show_version);
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(parse_error(&["-Y", "foo"]).starts_with("invalid option -- 'Y'\nUsage: grep"));
+    assert!(parse_error(&["--nope", "foo"]).starts_with("unrecognized option '--nope'"));
+    assert!(parse_error(&["foo", "-m"]).starts_with("option requires an argument -- 'm'"));
+    assert!(parse_error(&["foo", "--max-count"]).starts_with("option '--max-count' requires an argument"));
+    assert!(parse_error(&["--count=3", "foo"]).starts_with("option '--count' doesn't allow an argument"));
+    assert!(parse_error(&["-m", "x", "foo"]).starts_with("invalid argument \"x\" for '--max-count'"));
+    assert!(parse_error(&["--color=sometimes", "foo"]).starts_with("Invalid color choice"));
+    assert!(parse_error(&["-c"]).starts_with("no pattern given"));
+}
+
+#[test]
+fn test_parse_binary_files() {
+    assert_eq!(parse(&["foo"]).binary_files, BinaryFiles::Binary);
+    assert_eq!(parse(&["-a", "foo"]).binary_files, BinaryFiles::Text);
+    assert_eq!(parse(&["--text", "foo"]).binary_files, BinaryFiles::Text);
+    assert_eq!(parse(&["-cI", "foo"]).binary_files, BinaryFiles::WithoutMatch);
+    assert_eq!(parse(&["--binary-files", "without-match", "foo"]).binary_files, BinaryFiles::WithoutMatch);
+    assert!(parse_error(&["--binary-files=maybe", "foo"]).starts_with("invalid argument \"maybe\" for '--binary-files'"));
+}
+
+#[test]
+fn test_parse_null() {
+    let options = parse(&["-zZ", "foo"]);
+    assert!(options.null_data && options.null);
+    let options = parse(&["--null-data", "foo"]);
+    assert!(options.null_data && !options.null);
+}
+
+#[test]
+fn test_parse_json() {
+    assert!(parse(&["--json", "foo"]).json);
+    assert!(parse_error(&["--json", "-c", "foo"]).starts_with("--json can't be combined"));
+}
+
+#[test]
+fn test_parse_dump_ast() {
+    assert_eq!(parse(&["--dump-ast=json", "foo"]).dump_ast, Some(AstFormat::Json));
+    assert_eq!(parse(&["foo"]).dump_ast, None);
+    assert!(parse_error(&["--dump-ast=yaml", "foo"]).starts_with("invalid argument \"yaml\" for '--dump-ast'"));
+}
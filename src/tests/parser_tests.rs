@@ -90,11 +90,17 @@ fn test_parse_negated_character_set() {
 fn test_parse_alternation() {
     assert_eq!(
         parse_pattern("(a|b)").unwrap(),
-        Pattern::Alternation(vec![
+        Pattern::CaptureGroup(Box::new(Pattern::OneOf(vec![
             Pattern::ExactChar('a'),
             Pattern::ExactChar('b')
-        ])
+        ])))
     );
+    // Only parentheses make a group
+    assert_eq!(
+        parse_pattern("a|b").unwrap(),
+        Pattern::OneOf(vec![Pattern::ExactChar('a'), Pattern::ExactChar('b')])
+    );
+    assert_eq!(parse_pattern("(?:a|b)").unwrap(), parse_pattern("a|b").unwrap());
 }
 
 #[test]
@@ -136,4 +142,70 @@ fn test_parse_errors() {
     assert!(parse_pattern("[abc").is_err());
     assert!(parse_pattern("\\").is_err());
     assert!(parse_pattern("*").is_err());
-}
\ No newline at end of file
+}
+#[test]
+fn test_parse_exact_repeat() {
+    assert_eq!(
+        parse_pattern("a{2}").unwrap(),
+        Pattern::Repeated {
+            min: 2,
+            max: Some(2),
+            pattern: Box::new(Pattern::ExactChar('a'))
+        }
+    );
+    assert_eq!(
+        parse_pattern("a{0,2}").unwrap(),
+        Pattern::Repeated {
+            min: 0,
+            max: Some(2),
+            pattern: Box::new(Pattern::ExactChar('a'))
+        }
+    );
+}
+
+#[test]
+fn test_parse_unbalanced_groups() {
+    assert!(parse_pattern("(ab").is_err());
+    assert!(parse_pattern("ab)").is_err());
+    assert!(parse_pattern("()").is_err());
+}
+
+#[test]
+fn test_parse_empty() {
+    assert_eq!(parse_pattern("").unwrap(), Pattern::Sequence(vec![]));
+}
+
+#[test]
+fn test_parse_flags() {
+    assert_eq!(parse_pattern("(?s).").unwrap(), Pattern::CharacterSet { chars: String::new(), negated: true });
+    assert_eq!(parse_pattern("(?s)(?-s).").unwrap(), Pattern::AnyChar);
+    assert_eq!(parse_pattern("\\n").unwrap(), Pattern::ExactChar('\n'));
+    assert!(parse_pattern("(?i)a").is_err());
+    assert!(parse_pattern("(?s").is_err());
+}
+
+#[test]
+fn test_parse_named_groups() {
+    let pattern = parse_pattern("(?<year>\\d)-(?P<month>\\d)(x)").unwrap();
+    assert_eq!(pattern.capture_names(), vec![Some("year"), Some("month"), None]);
+    assert_eq!(pattern.capture_index("month"), Some(2));
+    assert!(matches!(&pattern, Pattern::Sequence(parts) if matches!(&parts[0], Pattern::NamedCapture { name, .. } if name == "year")));
+    assert!(parse_pattern("(?<1a>x)").is_err());
+    assert!(parse_pattern("(?<a>x").is_err());
+    assert!(parse_pattern("(?P)").is_err());
+}
+
+#[test]
+fn test_parse_non_capturing_groups() {
+    assert_eq!(parse_pattern("(?:a)").unwrap(), Pattern::ExactChar('a'));
+    assert_eq!(
+        parse_pattern("(?:a|b)").unwrap(),
+        Pattern::OneOf(vec![Pattern::ExactChar('a'), Pattern::ExactChar('b')])
+    );
+    assert_eq!(
+        parse_pattern("\\b{start-half}a\\b{end-half}").unwrap(),
+        Pattern::Sequence(vec![Pattern::NoWordBefore, Pattern::ExactChar('a'), Pattern::NoWordAfter])
+    );
+    assert_eq!(parse_pattern("\\b").unwrap(), Pattern::ExactChar('b'));
+    assert!(parse_pattern("(?:)").is_err());
+}
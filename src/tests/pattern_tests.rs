@@ -40,6 +40,14 @@ fn test_pattern_partial_ord() {
     assert!(Pattern::AlphaNumeric <= Pattern::AlphaNumeric);
 }
 
+#[test]
+fn test_literal() {
+    assert_eq!(Pattern::literal(""), Pattern::Sequence(vec![]));
+    assert_eq!(Pattern::literal("*"), Pattern::ExactChar('*'));
+    assert_eq!(Pattern::literal("a.b"), Pattern::Literal("a.b".to_string()));
+    assert_eq!(Pattern::literal("(x)").whole_word().to_string(), "\\b{start-half}\\(x\\)\\b{end-half}");
+}
+
 #[test]
 fn test_nested_patterns() {
     let nested_pattern = Pattern::OneOrMore(Box::new(Pattern::Alternation(vec![
@@ -93,4 +101,36 @@ fn test_nested_capture_creation() {
         Pattern::ExactChar('b'),
         Pattern::ExactChar('a')
     ]))));
-}
\ No newline at end of file
+}
+#[test]
+fn test_whole_line() {
+    assert_eq!(
+        Pattern::ExactChar('a').whole_line(),
+        Pattern::Sequence(vec![Pattern::StartOfLine, Pattern::ExactChar('a'), Pattern::EndOfLine])
+    );
+    // A choice is restricted as a whole without becoming a capture group
+    assert_eq!(
+        Pattern::OneOf(vec![Pattern::ExactChar('a'), Pattern::ExactChar('b')]).whole_word(),
+        Pattern::Sequence(vec![
+            Pattern::NoWordBefore,
+            Pattern::OneOf(vec![Pattern::ExactChar('a'), Pattern::ExactChar('b')]),
+            Pattern::NoWordAfter
+        ])
+    );
+}
+
+#[test]
+fn test_any_of() {
+    let combined = Pattern::any_of(vec![
+        Pattern::Sequence(vec![Pattern::CaptureGroup(Box::new(Pattern::ExactChar('a'))), Pattern::Backreference(1)]),
+        Pattern::Sequence(vec![Pattern::CaptureGroup(Box::new(Pattern::ExactChar('b'))), Pattern::Backreference(1)]),
+    ]);
+    assert_eq!(
+        combined,
+        Pattern::OneOf(vec![
+            Pattern::Sequence(vec![Pattern::CaptureGroup(Box::new(Pattern::ExactChar('a'))), Pattern::Backreference(1)]),
+            Pattern::Sequence(vec![Pattern::CaptureGroup(Box::new(Pattern::ExactChar('b'))), Pattern::Backreference(2)]),
+        ])
+    );
+    assert_eq!(Pattern::any_of(vec![Pattern::AnyChar]), Pattern::AnyChar);
+}
@@ -0,0 +1,84 @@
+use crate::reader::{LineEndings, LineReader};
+use std::io::{self, Read};
+
+// Reads at most `step` bytes at a time, like a slow pipe
+struct Trickle<'a> {
+    data: &'a [u8],
+    step: usize,
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.step.min(buf.len()).min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+fn read_lines(reader: impl Read) -> Vec<(usize, Vec<u8>)> {
+    let mut reader = LineReader::new(reader, b'\n');
+    let mut lines = vec![];
+    while let Some(line) = reader.next_line().unwrap() {
+        lines.push((line.offset, line.bytes.to_vec()));
+    }
+    lines
+}
+
+#[test]
+fn test_read_lines() {
+    assert_eq!(
+        read_lines(&b"ab\ncd\n\nef"[..]),
+        vec![(0, b"ab\n".to_vec()), (3, b"cd\n".to_vec()), (6, b"\n".to_vec()), (7, b"ef".to_vec())]
+    );
+    assert!(read_lines(&b""[..]).is_empty());
+}
+
+#[test]
+fn test_read_lines_in_small_chunks() {
+    let data = b"first line\nsecond\nthird line here\n";
+    let lines = read_lines(Trickle { data, step: 4 });
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[1], (11, b"second\n".to_vec()));
+    assert_eq!(lines[2], (18, b"third line here\n".to_vec()));
+}
+
+#[test]
+fn test_read_long_line() {
+    let long = "x".repeat(200_000);
+    let data = format!("a\n{}\nb", long);
+    let lines = read_lines(Trickle { data: data.as_bytes(), step: 50_000 });
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[1].1.len(), long.len() + 1);
+    assert_eq!(lines[2], (long.len() + 3, b"b".to_vec()));
+}
+
+#[test]
+fn test_seen_nul() {
+    // A NUL is noticed as soon as the chunk holding it is read
+    let mut reader = LineReader::new(&b"text\nbin\0ary\n"[..], b'\n');
+    assert!(reader.next_line().unwrap().unwrap().seen_nul);
+
+    let data = format!("text\n{}\0\n", "x".repeat(100_000));
+    let mut reader = LineReader::new(Trickle { data: data.as_bytes(), step: 10 }, b'\n');
+    assert!(!reader.next_line().unwrap().unwrap().seen_nul);
+    assert!(reader.next_line().unwrap().unwrap().seen_nul);
+}
+
+#[test]
+fn test_read_null_terminated() {
+    let mut reader = LineReader::new(&b"a\nb\0c\0"[..], b'\0');
+    let line = reader.next_line().unwrap().unwrap();
+    assert_eq!(line.bytes, b"a\nb\0");
+    assert!(!line.seen_nul);
+    assert_eq!(reader.next_line().unwrap().unwrap().bytes, b"c\0");
+    assert!(reader.next_line().unwrap().is_none());
+}
+
+#[test]
+fn test_detect_line_endings() {
+    assert!(!LineEndings::detect(b"a\nb\n").is_mixed());
+    assert!(!LineEndings::detect(b"a\r\nb\r\nc").is_mixed());
+    assert!(LineEndings::detect(b"a\r\nb\n").is_mixed());
+    assert_eq!(LineEndings::detect(b"a\r"), LineEndings::default());
+}
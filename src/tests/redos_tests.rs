@@ -0,0 +1,26 @@
+use crate::parser::parse_pattern;
+
+fn risky(regex: &str) -> Vec<String> {
+    let pattern = parse_pattern(regex).unwrap();
+    pattern.backtrack_risks().iter().map(|risk| risk.inner.to_string()).collect()
+}
+
+#[test]
+fn test_nested_repetitions() {
+    assert_eq!(risky("(a+)+b"), vec!["a+"]);
+    assert_eq!(risky("(a*)*"), vec!["a*"]);
+    assert_eq!(risky("^(x?\\w+)*$"), vec!["\\w+"]);
+    assert_eq!(risky("(?:a|(b+)){2,}"), vec!["b+"]);
+    assert_eq!(risky("((ab)+)+"), vec!["(ab)+"]);
+}
+
+#[test]
+fn test_unambiguous_repetitions() {
+    // Something required separates the inner repetitions of consecutive iterations
+    assert!(risky("(a+,)+").is_empty());
+    assert!(risky("(\\d+-\\d+)*").is_empty());
+    // The outer repetition runs at most once
+    assert!(risky("(a+)?b").is_empty());
+    assert!(risky("(a+){1}").is_empty());
+    assert!(risky("a+b+").is_empty());
+}
@@ -0,0 +1,78 @@
+use crate::{MatchConfig, MatchError, Pattern, Regex, RegexBuilder};
+
+#[test]
+fn test_regex_matching() {
+    let regex = Regex::new("(\\d+)-(\\d+)").unwrap();
+    assert_eq!(regex.is_match("a 12-34"), Ok(true));
+    assert_eq!(regex.find("a 12-34"), Ok(Some(2..7)));
+    assert_eq!(regex.find_at("12-34 5-6", 3), Ok(Some(6..9)));
+    let captures = regex.captures("a 12-34").unwrap().unwrap();
+    assert_eq!(captures.get(2), Some(5..7));
+    assert_eq!(Regex::from(Pattern::ExactChar('x')).find("ax"), Ok(Some(1..2)));
+    assert!(Regex::new("(a").is_err());
+}
+
+#[test]
+fn test_regex_config() {
+    let config = MatchConfig {
+        line_terminator: Some('\n'),
+        ..MatchConfig::default()
+    };
+    let regex = RegexBuilder::new("^b$").config(config).build().unwrap();
+    assert_eq!(regex.find("a\nb\nc"), Ok(Some(2..3)));
+}
+
+#[test]
+fn test_backtrack_limit() {
+    let text = "a".repeat(30);
+    let regex = RegexBuilder::new("(a+)+b").backtrack_limit(10_000).build().unwrap();
+    assert_eq!(regex.is_match(&text), Err(MatchError::BudgetExceeded));
+    // Matches within the budget are still found
+    assert_eq!(regex.find("aaab"), Ok(Some(0..4)));
+    let regex = RegexBuilder::new("a+b").backtrack_limit(10_000).build().unwrap();
+    assert_eq!(regex.is_match(&text), Ok(false));
+}
+
+fn split(regex: &str, text: &str) -> Vec<String> {
+    let regex = Regex::new(regex).unwrap();
+    regex.split(text).map(|piece| piece.unwrap().to_string()).collect()
+}
+
+fn splitn(regex: &str, text: &str, limit: usize) -> Vec<String> {
+    let regex = Regex::new(regex).unwrap();
+    regex.splitn(text, limit).map(|piece| piece.unwrap().to_string()).collect()
+}
+
+#[test]
+fn test_split() {
+    assert_eq!(split(" *, *", "a, b ,c"), vec!["a", "b", "c"]);
+    assert_eq!(split(",", ",a,,b,"), vec!["", "a", "", "b", ""]);
+    assert_eq!(split("x", ""), vec![""]);
+    assert_eq!(split("x", "abc"), vec!["abc"]);
+}
+
+#[test]
+fn test_split_empty_matches() {
+    assert_eq!(split("", "abc"), vec!["", "a", "b", "c", ""]);
+    assert_eq!(split("", "é!"), vec!["", "é", "!", ""]);
+    // No empty match right after a non-empty one
+    assert_eq!(split("-*", "a--b"), vec!["", "a", "b", ""]);
+    assert_eq!(split("\\d*", "a12b"), vec!["", "a", "b", ""]);
+}
+
+#[test]
+fn test_splitn() {
+    assert_eq!(splitn("=", "key=value=more", 2), vec!["key", "value=more"]);
+    assert_eq!(splitn("=", "a=b", 5), vec!["a", "b"]);
+    assert_eq!(splitn("=", "a=b", 1), vec!["a=b"]);
+    assert!(splitn("=", "a=b", 0).is_empty());
+    assert_eq!(splitn("", "abc", 3), vec!["", "a", "bc"]);
+}
+
+#[test]
+fn test_split_budget_exceeded() {
+    let regex = RegexBuilder::new("(a+)+b").backtrack_limit(10_000).build().unwrap();
+    let text = format!("x {}", "a".repeat(30));
+    let pieces: Vec<_> = regex.split(&text).collect();
+    assert_eq!(pieces, vec![Err(MatchError::BudgetExceeded)]);
+}
@@ -0,0 +1,33 @@
+use crate::parser::parse_pattern;
+use crate::replace::Template;
+use crate::Matcher;
+
+fn expand(pattern: &str, template: &str, text: &str) -> String {
+    let pattern = parse_pattern(pattern).unwrap();
+    let captures = Matcher::captures(&pattern, text).unwrap();
+    Template::new(template, &pattern).expand(text, &captures)
+}
+
+#[test]
+fn test_expand_groups() {
+    assert_eq!(expand("(\\w+)@(\\w+)", "$2 at $1", "me@host"), "host at me");
+    assert_eq!(expand("(\\w+)@(\\w+)", "${1}_$0", "me@host"), "me_me@host");
+    assert_eq!(expand("(?<user>\\w+)@(?P<host>\\w+)", "$host/${user}", "me@host"), "host/me");
+    // A named choice is one group, so the groups after it keep their numbers
+    assert_eq!(expand("(?<n>a|b)(c)", "[$2]", "ac"), "[c]");
+    assert_eq!(expand("(?<n>a|b)(c)", "[$n$1]", "bc"), "[bb]");
+}
+
+#[test]
+fn test_expand_missing_groups() {
+    // Like ripgrep, `$1a` names a group "1a" which doesn't exist
+    assert_eq!(expand("(a)", "[$1a]", "a"), "[]");
+    assert_eq!(expand("(a)", "[$2][$nope]", "a"), "[][]");
+    assert_eq!(expand("(a)|b", "[$1]", "b"), "[]");
+}
+
+#[test]
+fn test_expand_literal_dollars() {
+    assert_eq!(expand("a", "$$1", "a"), "$1");
+    assert_eq!(expand("a", "$ ${ $", "a"), "$ ${ $");
+}
@@ -0,0 +1,410 @@
+use crate::color::Colors;
+use crate::options::Options;
+use crate::search::Searcher;
+use std::time::Duration;
+use crate::{parse_patterns, search_pattern, Pattern};
+
+fn build_pattern(options: &Options) -> Pattern {
+    search_pattern(options, parse_patterns(options).unwrap())
+}
+
+fn search(args: &[&str], with_filename: bool, input: &str) -> (usize, String) {
+    search_with_colors(args, with_filename, None, input)
+}
+
+fn search_with_colors(args: &[&str], with_filename: bool, colors: Option<Colors>, input: &str) -> (usize, String) {
+    let options = Options::parse(args.iter().map(|s| s.to_string())).unwrap();
+    let pattern = build_pattern(&options);
+    let mut searcher = Searcher::new(&pattern, &options, with_filename);
+    if let Some(colors) = colors {
+        searcher = searcher.with_colors(colors);
+    }
+    let mut out = Vec::new();
+    let count = searcher.search("input.txt", input.as_bytes(), &mut out).unwrap();
+
+    // Searching the whole buffer at once gives the same results as streaming it
+    let mut buffer_out = Vec::new();
+    let buffer_count = searcher.search_buffer("input.txt", input.as_bytes(), &mut buffer_out).unwrap();
+    assert_eq!((buffer_count, &buffer_out), (count, &out), "buffer search differs for {:?}", args);
+
+    (count, String::from_utf8(out).unwrap())
+}
+
+const INPUT: &str = "foo bar\nbaz\nfoo foo\n";
+
+#[test]
+fn test_search_lines() {
+    assert_eq!(search(&["-E", "foo"], false, INPUT), (2, "foo bar\nfoo foo\n".to_string()));
+    assert_eq!(search(&["foo"], true, INPUT), (2, "input.txt:foo bar\ninput.txt:foo foo\n".to_string()));
+    assert_eq!(search(&["qux"], false, INPUT), (0, String::new()));
+}
+
+#[test]
+fn test_search_count() {
+    assert_eq!(search(&["-c", "foo"], false, INPUT), (2, "2\n".to_string()));
+    assert_eq!(search(&["-c", "qux"], true, INPUT), (0, "input.txt:0\n".to_string()));
+}
+
+#[test]
+fn test_search_files() {
+    assert_eq!(search(&["-l", "foo"], false, INPUT), (1, "input.txt\n".to_string()));
+    assert_eq!(search(&["-l", "qux"], false, INPUT), (0, String::new()));
+    assert_eq!(search(&["-L", "foo"], false, INPUT), (1, String::new()));
+    assert_eq!(search(&["-L", "qux"], false, INPUT), (0, "input.txt\n".to_string()));
+    assert_eq!(search(&["-q", "foo"], false, INPUT), (1, String::new()));
+}
+
+#[test]
+fn test_search_only_matching() {
+    assert_eq!(search(&["-o", "fo+"], false, INPUT), (2, "foo\nfoo\nfoo\n".to_string()));
+    assert_eq!(search(&["-o", "b*"], false, INPUT), (3, "b\nb\n".to_string()));
+}
+
+#[test]
+fn test_search_max_count() {
+    assert_eq!(search(&["-m", "1", "foo"], false, INPUT), (1, "foo bar\n".to_string()));
+    assert_eq!(search(&["-m", "0", "foo"], false, INPUT), (0, String::new()));
+    assert_eq!(search(&["-c", "-m", "1", "foo"], false, INPUT), (1, "1\n".to_string()));
+}
+
+#[test]
+fn test_search_line_numbers() {
+    assert_eq!(search(&["-n", "foo"], false, INPUT), (2, "1:foo bar\n3:foo foo\n".to_string()));
+    assert_eq!(search(&["-n", "-c", "foo"], false, INPUT), (2, "2\n".to_string()));
+}
+
+#[test]
+fn test_search_byte_offsets() {
+    assert_eq!(search(&["-b", "ba"], false, INPUT), (2, "0:foo bar\n8:baz\n".to_string()));
+    assert_eq!(search(&["-b", "-o", "foo"], false, INPUT), (2, "0:foo\n12:foo\n16:foo\n".to_string()));
+}
+
+#[test]
+fn test_search_columns() {
+    assert_eq!(search(&["--column", "ba"], false, INPUT), (2, "5:foo bar\n1:baz\n".to_string()));
+    assert_eq!(
+        search(&["--vimgrep", "foo"], true, INPUT),
+        (2, "input.txt:1:1:foo bar\ninput.txt:3:1:foo foo\ninput.txt:3:5:foo foo\n".to_string())
+    );
+}
+
+const CONTEXT_INPUT: &str = "a\nb\nmatch 1\nc\nd\ne\nf\nmatch 2\ng\n";
+
+#[test]
+fn test_search_after_context() {
+    assert_eq!(
+        search(&["-A", "1", "match"], false, CONTEXT_INPUT).1,
+        "match 1\nc\n--\nmatch 2\ng\n"
+    );
+    assert_eq!(
+        search(&["-n", "-A", "1", "-m", "1", "match"], false, CONTEXT_INPUT).1,
+        "3:match 1\n4-c\n"
+    );
+}
+
+#[test]
+fn test_search_before_context() {
+    assert_eq!(
+        search(&["-B", "2", "match"], false, CONTEXT_INPUT).1,
+        "a\nb\nmatch 1\n--\ne\nf\nmatch 2\n"
+    );
+}
+
+#[test]
+fn test_search_context_groups() {
+    // Adjacent groups are merged without a separator
+    assert_eq!(
+        search(&["-n", "-C", "2", "match"], true, CONTEXT_INPUT).1,
+        "input.txt-1-a\ninput.txt-2-b\ninput.txt:3:match 1\ninput.txt-4-c\ninput.txt-5-d\n\
+         input.txt-6-e\ninput.txt-7-f\ninput.txt:8:match 2\ninput.txt-9-g\n"
+    );
+    assert_eq!(
+        search(&["-C", "1", "--group-separator=::", "match"], false, CONTEXT_INPUT).1,
+        "b\nmatch 1\nc\n::\nf\nmatch 2\ng\n"
+    );
+    assert_eq!(
+        search(&["-C", "1", "--no-group-separator", "match"], false, CONTEXT_INPUT).1,
+        "b\nmatch 1\nc\nf\nmatch 2\ng\n"
+    );
+    assert_eq!(
+        search(&["-C", "1", "-A", "0", "match"], false, CONTEXT_INPUT).1,
+        "b\nmatch 1\n--\nf\nmatch 2\n"
+    );
+}
+
+#[test]
+fn test_search_invert_match() {
+    assert_eq!(search(&["-v", "foo"], false, INPUT), (1, "baz\n".to_string()));
+    assert_eq!(search(&["-v", "-c", "a"], false, INPUT), (1, "1\n".to_string()));
+    assert_eq!(search(&["-v", "-n", "-A", "1", "ba"], false, INPUT), (1, "3:foo foo\n".to_string()));
+}
+
+#[test]
+fn test_search_word_regexp() {
+    assert_eq!(search(&["-w", "ba"], false, INPUT), (0, String::new()));
+    assert_eq!(search(&["-w", "bar|baz"], false, INPUT), (2, "foo bar\nbaz\n".to_string()));
+    // A later whole-word match is found after an earlier partial one
+    assert_eq!(search(&["-w", "-o", "foo"], false, "foobar foo\n"), (1, "foo\n".to_string()));
+    assert_eq!(search(&["-w", "-o", "\\w+o"], false, "food foo\n"), (1, "foo\n".to_string()));
+}
+
+#[test]
+fn test_search_line_regexp() {
+    assert_eq!(search(&["-x", "ba."], false, INPUT), (1, "baz\n".to_string()));
+    assert_eq!(search(&["-x", "foo"], false, INPUT), (0, String::new()));
+    assert_eq!(search(&["-x", "baz|foo.*"], false, INPUT), (3, INPUT.to_string()));
+}
+
+#[test]
+fn test_search_colors() {
+    let colors = Colors::parse("ne");
+    assert_eq!(
+        search_with_colors(&["-n", "o+"], true, Some(colors.clone()), "foo bar foo\n").1,
+        "\x1b[35minput.txt\x1b[m\x1b[36m:\x1b[m\x1b[32m1\x1b[m\x1b[36m:\x1b[m\
+         f\x1b[01;31moo\x1b[m bar f\x1b[01;31moo\x1b[m\n"
+    );
+    assert_eq!(
+        search_with_colors(&["-o", "ba."], false, Some(colors.clone()), INPUT).1,
+        "\x1b[01;31mbar\x1b[m\n\x1b[01;31mbaz\x1b[m\n"
+    );
+    assert_eq!(
+        search_with_colors(&["-A", "1", "-m", "1", "ba"], false, Some(Colors::parse("ne:mc=33:cx=2")), "a\nbar\nqux\n").1,
+        "\x1b[01;31mba\x1b[mr\n\x1b[2mqux\x1b[m\n"
+    );
+}
+
+#[test]
+fn test_search_multiple_patterns() {
+    assert_eq!(search(&["-e", "bar", "-e", "baz"], false, INPUT), (2, "foo bar\nbaz\n".to_string()));
+    assert_eq!(search(&["-o", "-e", "(o)\\1", "-e", "(z)$"], false, INPUT), (3, "oo\nz\noo\noo\n".to_string()));
+    assert_eq!(search(&["-e", "qux", "-e", ""], false, INPUT), (3, INPUT.to_string()));
+}
+
+#[test]
+fn test_search_pattern_file() {
+    let path = std::env::temp_dir().join(format!("grep-patterns-{}", std::process::id()));
+    std::fs::write(&path, "bar\nqux\n").unwrap();
+    let file = path.to_str().unwrap();
+    assert_eq!(search(&["-f", file], false, INPUT), (1, "foo bar\n".to_string()));
+    assert_eq!(search(&["-f", file, "-e", "baz"], false, INPUT), (2, "foo bar\nbaz\n".to_string()));
+    std::fs::write(&path, "").unwrap();
+    assert_eq!(search(&["-f", file], false, INPUT), (0, String::new()));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_search_buffer_anchors() {
+    // Anchors match at line boundaries within the buffer and no match spans lines
+    assert_eq!(search(&["-n", "^b"], false, INPUT), (1, "2:baz\n".to_string()));
+    assert_eq!(search(&["-n", "r$"], false, INPUT), (1, "1:foo bar\n".to_string()));
+    assert_eq!(search(&["-c", "bar.baz"], false, INPUT), (0, "0\n".to_string()));
+    assert_eq!(search(&["-c", "^$"], false, "a\n\nb\n"), (1, "1\n".to_string()));
+    assert_eq!(search(&["-n", "x*"], false, "a\nb"), (2, "1:a\n2:b\n".to_string()));
+    assert_eq!(search(&["-v", "-n", "b"], false, "a\nb\nc"), (2, "1:a\n3:c\n".to_string()));
+}
+
+const BINARY_INPUT: &str = "foo\0bar\nbaz\nfoo again\n";
+
+#[test]
+fn test_search_binary() {
+    assert_eq!(search(&["foo"], false, BINARY_INPUT), (1, "Binary file input.txt matches\n".to_string()));
+    assert_eq!(search(&["-n", "-C", "1", "baz"], false, BINARY_INPUT), (1, "Binary file input.txt matches\n".to_string()));
+    assert_eq!(search(&["qux"], false, BINARY_INPUT), (0, String::new()));
+    // Modes that don't print lines work as usual
+    assert_eq!(search(&["-c", "foo"], false, BINARY_INPUT), (2, "2\n".to_string()));
+}
+
+#[test]
+fn test_search_binary_as_text() {
+    assert_eq!(search(&["-a", "-n", "ba"], false, BINARY_INPUT), (2, "1:foo\0bar\n2:baz\n".to_string()));
+    assert_eq!(search(&["--binary-files=text", "-o", "ba."], false, BINARY_INPUT).1, "bar\nbaz\n");
+}
+
+#[test]
+fn test_search_binary_late() {
+    // A NUL past the first chunk makes the input binary from the line read along with it
+    let input = format!("{}bar\0\nfoo\n", "foo\n".repeat(20_000));
+    let (count, out) = search(&["foo"], false, &input);
+    assert_eq!(count, 16_385);
+    assert_eq!(out, format!("{}Binary file input.txt matches\n", "foo\n".repeat(16_384)));
+    assert_eq!(search(&["-c", "foo"], false, &input).0, 20_001);
+    assert_eq!(search(&["-I", "-c", "foo"], false, &input).0, 16_384);
+    // Both searches count the bytes they read before stopping
+    let end = search_json(&["--json", "-I", "o"], input.as_bytes()).pop().unwrap();
+    assert!(end.contains(r#""binary_offset":65536,"#), "{}", end);
+    assert!(end.contains(r#""bytes_searched":65540,"#), "{}", end);
+    let end = search_json(&["--json", "-m", "1", "bar"], format!("bar\n{}bar\n", "foo\n".repeat(10)).as_bytes()).pop().unwrap();
+    assert!(end.contains(r#""bytes_searched":8,"#), "{}", end);
+}
+
+#[test]
+fn test_search_binary_without_match() {
+    assert_eq!(search(&["-I", "foo"], false, BINARY_INPUT), (0, String::new()));
+    assert_eq!(search(&["--binary-files=without-match", "-L", "foo"], false, BINARY_INPUT), (0, "input.txt\n".to_string()));
+    assert_eq!(search(&["-I", "foo"], false, INPUT).0, 2);
+}
+
+// Output of both searches of input that need not be UTF-8
+fn search_bytes(args: &[&str], input: &[u8]) -> Vec<u8> {
+    let options = Options::parse(args.iter().map(|s| s.to_string())).unwrap();
+    let pattern = build_pattern(&options);
+    let searcher = Searcher::new(&pattern, &options, false);
+    let mut out = Vec::new();
+    searcher.search("input.txt", input, &mut out).unwrap();
+    let mut buffer_out = Vec::new();
+    searcher.search_buffer("input.txt", input, &mut buffer_out).unwrap();
+    assert_eq!(buffer_out, out, "buffer search differs for {:?}", args);
+    out
+}
+
+#[test]
+fn test_search_invalid_utf8() {
+    let input = b"b\xffr\nbar\n";
+    assert_eq!(search_bytes(&["-a", "b.r"], input), input);
+    // Invalid UTF-8 alone makes an input binary
+    assert_eq!(search_bytes(&["b.r"], input), b"Binary file input.txt matches\n");
+
+    // Lines and offsets are printed as in the input, not as in its decoded text
+    let input = b"caf\xe9 x\n";
+    assert_eq!(search_bytes(&["-a", "x"], input), input);
+    assert_eq!(search_bytes(&["-a", "-b", "-o", "x"], input), b"5:x\n");
+    assert_eq!(search_bytes(&["-a", "-U", "-b", "-o", "x"], input), b"5:x\n");
+    assert_eq!(search_bytes(&["-a", "--vimgrep", "x"], input), b"1:6:caf\xe9 x\n");
+}
+
+#[test]
+fn test_search_null_data() {
+    let input = "foo\nbar\0baz\0foo\0";
+    assert_eq!(search(&["-z", "bar"], false, input), (1, "foo\nbar\0".to_string()));
+    assert_eq!(search(&["-z", "-n", "^foo$"], false, input), (1, "3:foo\0".to_string()));
+    assert_eq!(search(&["-z", "-c", "o"], false, input), (2, "2\n".to_string()));
+    // NUL bytes don't make the input binary when they end lines
+    assert_eq!(search(&["-z", "-o", "ba."], false, input).1, "bar\0baz\0");
+}
+
+#[test]
+fn test_search_null_after_filename() {
+    assert_eq!(search(&["-Z", "baz"], true, INPUT).1, "input.txt\0baz\n");
+    assert_eq!(search(&["-Zl", "foo"], false, INPUT).1, "input.txt\0");
+    assert_eq!(search(&["--null", "-c", "foo"], true, INPUT).1, "input.txt\x002\n");
+}
+
+#[test]
+fn test_search_crlf() {
+    let input = "foo\r\nbar\r\nfoo bar\r\n";
+    assert_eq!(search(&["bar$"], false, input), (0, String::new()));
+    assert_eq!(search(&["--crlf", "-n", "bar$"], false, input), (2, "2:bar\n3:foo bar\n".to_string()));
+    assert_eq!(search(&["--crlf", "-o", "o.$"], false, input).1, "oo\n");
+    assert_eq!(search(&["--crlf", "-x", "foo"], false, "foo\r\nfoo\n").0, 2);
+}
+
+#[test]
+fn test_search_multiline() {
+    let input = "foo\nbar\nbaz\nfoo\n";
+    assert_eq!(search(&["foo\\nbar"], false, input), (0, String::new()));
+    assert_eq!(search(&["-U", "-n", "foo\\nbar"], false, input), (2, "1:foo\n2:bar\n".to_string()));
+    // A match ending with a newline doesn't touch the next line
+    assert_eq!(search(&["-U", "-n", "az\\n"], false, input).1, "3:baz\n");
+    assert_eq!(search(&["-U", "-o", "o\\nb"], false, input).1, "o\nb\n");
+    // `.` only matches a newline with the s flag
+    assert_eq!(search(&["-U", "-c", "r.b"], false, input).0, 0);
+    assert_eq!(search(&["-U", "-c", "(?s)r.b"], false, input).0, 2);
+    assert_eq!(search(&["-U", "-c", "^bar$"], false, input).0, 1);
+    // A match is colored on each of its lines, and replaced once where it starts
+    assert_eq!(
+        search_with_colors(&["-U", "o\\nb"], false, Some(Colors::parse("ne")), input).1,
+        "fo\x1b[01;31mo\x1b[m\n\x1b[01;31mb\x1b[mar\n"
+    );
+    assert_eq!(search(&["-U", "-r", "<$0>", "o\\nb"], false, input).1, "fo<o\nb>\nar\n");
+    assert_eq!(search(&["-U", "-o", "-r", "<$1>", "(o\\nb)"], false, input).1, "<o\nb>\n");
+}
+
+// JSON messages written by both searches, with their timings blanked out
+fn search_json(args: &[&str], input: &[u8]) -> Vec<String> {
+    fn blank_elapsed(mut json: String) -> String {
+        let mut from = 0;
+        while let Some(start) = json[from..].find("\"elapsed\":{").map(|i| from + i + "\"elapsed\":{".len()) {
+            let end = start + json[start..].find('}').unwrap();
+            json.replace_range(start..end, "");
+            from = start;
+        }
+        json
+    }
+
+    let options = Options::parse(args.iter().map(|s| s.to_string())).unwrap();
+    let pattern = build_pattern(&options);
+    let searcher = Searcher::new(&pattern, &options, false);
+    let mut out = Vec::new();
+    searcher.search("input.txt", input, &mut out).unwrap();
+    let mut buffer_out = Vec::new();
+    searcher.search_buffer("input.txt", input, &mut buffer_out).unwrap();
+    let out = blank_elapsed(String::from_utf8(out).unwrap());
+    assert_eq!(blank_elapsed(String::from_utf8(buffer_out).unwrap()), out);
+    out.lines().map(str::to_string).collect()
+}
+
+#[test]
+fn test_search_json() {
+    let messages = search_json(&["--json", "-A", "1", "match"], CONTEXT_INPUT.as_bytes());
+    assert_eq!(messages[0], r#"{"type":"begin","data":{"path":{"text":"input.txt"}}}"#);
+    assert!(messages[1].starts_with(r#"{"type":"match","data":{"path":{"text":"input.txt"},"lines":{"text":"#));
+    assert!(messages[1].ends_with(r#""submatches":[{"match":{"text":"match"},"start":0,"end":5}]}}"#));
+    assert!(messages.iter().any(|m| m.starts_with(r#"{"type":"context","#) && m.ends_with(r#""submatches":[]}}"#)));
+    let end = messages.last().unwrap();
+    assert!(end.starts_with(r#"{"type":"end","data":{"path":{"text":"input.txt"},"binary_offset":null,"stats":{"elapsed":{},"searches":1,"searches_with_match":1,"#));
+    assert!(!messages.iter().any(|m| m == "--"));
+}
+
+#[test]
+fn test_search_json_match() {
+    let messages = search_json(&["--json", "-a", "o+"], b"x\nfo\xff foo\n");
+    assert_eq!(
+        messages[1],
+        concat!(
+            r#"{"type":"match","data":{"path":{"text":"input.txt"},"lines":{"bytes":"Zm//IGZvbwo="},"#,
+            r#""line_number":2,"absolute_offset":2,"submatches":["#,
+            r#"{"match":{"text":"o"},"start":1,"end":2},{"match":{"text":"oo"},"start":5,"end":7}]}}"#
+        )
+    );
+    assert!(messages[2].contains(r#""bytes_searched":10,"bytes_printed":"#));
+    assert!(messages[2].ends_with(r#""matched_lines":1,"matches":2}}}"#));
+    // Nothing is written for inputs without a match
+    assert!(search_json(&["--json", "qux"], INPUT.as_bytes()).is_empty());
+}
+
+#[test]
+fn test_search_json_binary() {
+    // A match in binary input isn't printed, but its input still begins and ends
+    let messages = search_json(&["--json", "foo"], BINARY_INPUT.as_bytes());
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0], r#"{"type":"begin","data":{"path":{"text":"input.txt"}}}"#);
+    assert!(messages[1].starts_with(r#"{"type":"end","data":{"path":{"text":"input.txt"},"binary_offset":0,"#));
+    assert!(messages[1].contains(r#""matched_lines":1,"#));
+}
+
+#[test]
+fn test_search_json_summary() {
+    let options = Options::parse(["--json", "foo"].iter().map(|s| s.to_string())).unwrap();
+    let pattern = build_pattern(&options);
+    let searcher = Searcher::new(&pattern, &options, false);
+    let mut out = Vec::new();
+    searcher.search("input.txt", INPUT.as_bytes(), &mut out).unwrap();
+    searcher.write_summary(&mut out, Duration::ZERO).unwrap();
+
+    // The summary counts the bytes printed as the stats of each input do
+    let out = String::from_utf8(out).unwrap();
+    let bytes_printed = |message: &str| message.split(r#""bytes_printed":"#).nth(1).unwrap().split(',').next().unwrap().to_string();
+    let messages: Vec<_> = out.lines().collect();
+    let (end, summary) = (messages[messages.len() - 2], messages[messages.len() - 1]);
+    assert!(summary.starts_with(r#"{"type":"summary","#));
+    assert_eq!(bytes_printed(summary), bytes_printed(end));
+}
+
+#[test]
+fn test_search_replace() {
+    assert_eq!(search(&["-r", "[$0]", "fo+"], false, INPUT).1, "[foo] bar\n[foo] [foo]\n");
+    assert_eq!(search(&["-o", "--replace=$1", "f(o+)"], false, INPUT).1, "oo\noo\noo\n");
+    // Context lines are printed as they are
+    assert_eq!(search(&["-r", "X", "-A", "1", "bar"], false, INPUT).1, "foo X\nbaz\n");
+    assert_eq!(search(&["-r", "-", "^"], false, "ab\n").1, "-ab\n");
+}
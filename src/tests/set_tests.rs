@@ -0,0 +1,41 @@
+use crate::parser::parse_pattern;
+use crate::{Matcher, RegexSet};
+
+const PATTERNS: &[&str] = &[
+    "error",
+    "^warn",
+    "\\d{3}$",
+    "(foo|bar)+baz",
+    "\\b{start-half}id=\\w+",
+    "a*",
+    "(\\w+) \\1",
+    "[:=]x?",
+    "^$",
+];
+
+#[test]
+fn test_set_matches() {
+    let set = RegexSet::new(PATTERNS).unwrap();
+    assert_eq!(set.len(), PATTERNS.len());
+    assert_eq!(set.matches("warn: error 500"), vec![0, 1, 2, 5, 7]);
+    assert_eq!(set.matches("foobarbaz id=7"), vec![3, 4, 5, 7]);
+    assert_eq!(set.matches("the the"), vec![5, 6]);
+    assert_eq!(set.matches(""), vec![5, 8]);
+    assert!(!RegexSet::new(["x"]).unwrap().is_match("abc"));
+    assert!(RegexSet::new(["(a"]).is_err());
+}
+
+#[test]
+fn test_set_agrees_with_matcher() {
+    let set = RegexSet::new(PATTERNS).unwrap();
+    let texts = ["", "error", "a warning", "warn 12", "x 123", "idx=1 id=2", "barfoobaz", "aa aa", "é!"];
+    for text in texts {
+        let expected: Vec<_> = PATTERNS
+            .iter()
+            .enumerate()
+            .filter(|(_, regex)| Matcher::is_match(&parse_pattern(regex).unwrap(), text))
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(set.matches(text), expected, "{:?}", text);
+    }
+}